@@ -13,31 +13,150 @@
 // limitations under the License.
 
 use druid::{
+    commands,
     im::Vector,
-    widget::{Controller, EnvScope, Label, SizedBox},
-    AppDelegate, Application, Color, Data, DelegateCtx, Env, Event, EventCtx, KbKey, Point, Size,
-    Widget, WidgetExt, WindowDesc, WindowId,
+    widget::{
+        Button, Controller, CrossAxisAlignment, Either, EnvScope, Flex, Label, LineBreaking,
+        SizedBox, ZStack,
+    },
+    AppDelegate, Application, Command, Data, DelegateCtx, Env, Event, EventCtx, Handled, KbKey,
+    Lens, Point, Selector, Size, Target, TextAlignment, UnitPoint, UpdateCtx, Widget, WidgetExt,
+    WindowDesc, WindowId,
 };
 
-use crate::windows::Monitor;
-use crate::{config::Config, connection, palette};
+use crate::monitor::{Monitor, Monitors};
+use crate::{
+    config::Config,
+    connection,
+    keys::{self, Action},
+    minimap::{MiniMap, TOGGLE_MONITOR},
+    palette, platform,
+    reload::CONFIG_RELOADED,
+    selection::LastSelection,
+    settings::{self, Settings, ADD_PRESET, CLOSE_SETTINGS, SAVE_SETTINGS},
+    theme::Theme,
+    validation::{self, SelectionWarning},
+};
 
-#[derive(Clone, Data)]
+#[derive(Clone, Data, Lens)]
 pub struct State {
     config: Config,
     monitors: Vector<Monitor>,
     hovered_id: u32,
+    preset: Option<String>,
+    host: Option<String>,
+    warnings: Vector<String>,
+    config_errors: Vector<String>,
+    settings: Settings,
+    settings_monitor: Option<u32>,
+    help_open: bool,
+    details_open: bool,
+    hint_error: String,
 }
 
+const HOST_PICKED: Selector = Selector::new("instant-desktop.host-picked");
+
 impl State {
     pub fn new(config: Config, monitors: Vector<Monitor>, hovered_id: u32) -> Self {
         Self {
+            details_open: config.show_details,
+            config_errors: config_errors(&config, &monitors),
             config,
             monitors,
             hovered_id,
+            preset: None,
+            host: None,
+            warnings: Vector::new(),
+            settings: Settings::default(),
+            settings_monitor: None,
+            help_open: false,
+            hint_error: String::new(),
+        }
+    }
+
+    pub fn with_host(mut self, host: Option<String>) -> Self {
+        self.host = host;
+        self
+    }
+
+    pub fn set_config(&mut self, config: Config) {
+        // the details keep their toggled state unless the setting itself changes
+        if config.show_details != self.config.show_details {
+            self.details_open = config.show_details;
+        }
+
+        self.config_errors = config_errors(&config, &self.monitors);
+        self.config = config;
+    }
+
+    pub fn open_settings(&mut self) {
+        let monitor = self
+            .monitors
+            .iter()
+            .find(|monitor| monitor.id == self.hovered_id)
+            .or_else(|| self.monitors.front());
+
+        if let Some(monitor) = monitor {
+            self.settings = Settings::new(&self.config);
+            self.settings_monitor = Some(monitor.id);
+        }
+    }
+
+    pub fn close_settings(&mut self) {
+        self.settings_monitor = None;
+    }
+
+    pub fn save_settings(&mut self) {
+        let mut config = match self.settings.apply(&self.config) {
+            Ok(config) => config,
+            Err(err) => {
+                self.settings.error = err;
+                return;
+            }
+        };
+
+        let errors = config_errors(&config, &self.monitors);
+
+        if !errors.is_empty() {
+            self.settings.error = errors.into_iter().collect::<Vec<String>>().join("\n");
+            return;
+        }
+
+        let result = config
+            .mark_changes(&self.config)
+            .and_then(|_| config.save());
+
+        match result {
+            Ok(()) => {
+                self.set_config(config);
+                self.close_settings();
+            }
+            Err(err) => self.settings.error = err.to_string(),
         }
     }
 
+    pub fn dismiss_hint(&mut self) {
+        let mut config = self.config.clone();
+        config.hint_dismissed = true;
+
+        let result = config
+            .mark_changes(&self.config)
+            .and_then(|_| config.save());
+
+        // the hint stays until it's saved, so that hiding it can be tried again
+        match result {
+            Ok(()) => {
+                self.config = config;
+                self.hint_error.clear();
+            }
+            Err(err) => self.hint_error = err.to_string(),
+        }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.config.theme
+    }
+
     pub fn get_selected(&self) -> Vec<u32> {
         self.monitors
             .iter()
@@ -50,6 +169,127 @@ impl State {
             })
             .collect()
     }
+
+    pub fn selection_warnings(&self) -> Vec<SelectionWarning> {
+        validation::validate_selection(self.monitors.iter().filter(|monitor| monitor.selected))
+    }
+
+    pub fn toggle(&mut self, id: u32) {
+        for monitor in self.monitors.iter_mut() {
+            if monitor.id == id {
+                monitor.selected = !monitor.selected;
+            }
+        }
+
+        self.preset = None;
+        self.warnings.clear();
+    }
+
+    pub fn select_all(&mut self) {
+        self.set_selected(|_| true);
+    }
+
+    pub fn select_none(&mut self) {
+        self.set_selected(|_| false);
+    }
+
+    pub fn invert_selection(&mut self) {
+        self.set_selected(|monitor| !monitor.selected);
+    }
+
+    /// Favors the monitors that are aligned with the hovered one.
+    pub fn move_hover(&mut self, direction: Direction) {
+        let Some(hovered) = self
+            .monitors
+            .iter()
+            .find(|monitor| monitor.id == self.hovered_id)
+        else {
+            if let Some(monitor) = self.monitors.front() {
+                self.hovered_id = monitor.id;
+            }
+
+            return;
+        };
+
+        let (x, y) = hovered.rect().center();
+
+        let nearest = self
+            .monitors
+            .iter()
+            .filter_map(|monitor| {
+                let (other_x, other_y) = monitor.rect().center();
+                let (dx, dy) = (other_x - x, other_y - y);
+
+                let (distance, offset) = match direction {
+                    Direction::Left => (-dx, dy),
+                    Direction::Right => (dx, dy),
+                    Direction::Up => (-dy, dx),
+                    Direction::Down => (dy, dx),
+                };
+
+                (distance > 0).then(|| (distance + 2 * offset.abs(), monitor.id))
+            })
+            .min();
+
+        if let Some((_, id)) = nearest {
+            self.hovered_id = id;
+        }
+    }
+
+    fn set_selected(&mut self, selected: impl Fn(&Monitor) -> bool) {
+        for monitor in self.monitors.iter_mut() {
+            monitor.selected = selected(monitor);
+        }
+
+        self.preset = None;
+        self.warnings.clear();
+    }
+
+    pub fn apply_preset(&mut self, index: usize) {
+        if let Some(preset) = self.config.presets.get(index) {
+            for monitor in self.monitors.iter_mut() {
+                monitor.selected = preset.monitors.contains(&monitor.id);
+            }
+
+            self.preset = Some(preset.name.clone());
+            self.warnings.clear();
+        }
+    }
+
+    pub fn connection_config(&self) -> Config {
+        let config = match self.host.as_deref().and_then(|name| self.config.host(name)) {
+            Some(host) => self.config.for_host(host),
+            None => self.config.clone(),
+        };
+
+        match self.preset.as_deref().and_then(|name| config.preset(name)) {
+            Some(preset) => config.for_preset(preset),
+            None => config,
+        }
+    }
+}
+
+fn config_errors(config: &Config, monitors: &Vector<Monitor>) -> Vector<String> {
+    let mut list = Monitors::new();
+
+    for monitor in monitors {
+        list.add(monitor.clone());
+    }
+
+    config
+        .validate(Some(&list))
+        .into_iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .map(|diagnostic| diagnostic.to_string())
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
 }
 
 pub fn window_builder(
@@ -61,27 +301,13 @@ pub fn window_builder(
     let monitor = list.iter().find(|&mon| mon.id == id);
 
     if let Some(monitor) = monitor {
-        let (width, height, left, top) = if config.fullscreen {
-            (
-                monitor.width(),
-                monitor.height(),
-                monitor.left(),
-                monitor.top(),
-            )
-        } else {
-            (
-                monitor.work_width(),
-                monitor.work_height(),
-                monitor.work_left(),
-                monitor.work_top(),
-            )
-        };
+        let (size, position) = window_geometry(config, monitor);
 
         Some(
             WindowDesc::new(ui_builder(id))
                 .title("Instant Desktop")
-                .window_size(Size::new(width.into(), height.into()))
-                .set_position(Point::new(left.into(), top.into()))
+                .window_size(size)
+                .set_position(position)
                 .show_titlebar(false)
                 .resizable(false)
                 .set_always_on_top(true),
@@ -91,6 +317,65 @@ pub fn window_builder(
     }
 }
 
+pub fn host_picker_builder(config: &Config, monitors: &Vector<Monitor>) -> WindowDesc<State> {
+    let (width, height) = (400.0, 120.0 + 50.0 * config.hosts.len() as f64);
+
+    let mut content = Flex::column().with_child(Label::new("Connect to").with_text_size(32.0));
+
+    for host in &config.hosts {
+        let name = host.name.clone();
+
+        content = content.with_spacer(10.0).with_child(
+            Button::new(host.name.as_str()).fix_width(300.0).on_click(
+                move |ctx, data: &mut State, _| {
+                    data.host = Some(name.clone());
+                    ctx.submit_command(HOST_PICKED);
+                },
+            ),
+        );
+    }
+
+    let mut window = WindowDesc::new(
+        content
+            .center()
+            .expand()
+            .background(palette::PANEL_BACKGROUND_COLOR),
+    )
+    .title("Instant Desktop")
+    .window_size(Size::new(width, height))
+    .show_titlebar(false)
+    .resizable(false)
+    .set_always_on_top(true);
+
+    if let Some(primary) = monitors
+        .iter()
+        .find(|monitor| monitor.is_primary())
+        .or_else(|| monitors.front())
+    {
+        let (x, y) = primary.scaled_work_area().center();
+        window = window.set_position(Point::new(
+            f64::from(x) - width / 2.0,
+            f64::from(y) - height / 2.0,
+        ));
+    }
+
+    window
+}
+
+/// In the display points of the monitor, since the windows scale their geometry by its DPI.
+fn window_geometry(config: &Config, monitor: &Monitor) -> (Size, Point) {
+    let rect = if config.fullscreen {
+        monitor.scaled_rect()
+    } else {
+        monitor.scaled_work_area()
+    };
+
+    (
+        Size::new(rect.width().into(), rect.height().into()),
+        Point::new(rect.left.into(), rect.top.into()),
+    )
+}
+
 fn ui_builder(id: u32) -> impl Widget<State> {
     let id_label = Label::dynamic(move |data: &State, _| {
         if let Some(mon) = data.monitors.iter().find(|&mon| mon.id == id) {
@@ -99,23 +384,116 @@ fn ui_builder(id: u32) -> impl Widget<State> {
             String::from("error: monitor not found")
         }
     })
-    .with_text_size(200.0)
+    .with_text_size(palette::ID_FONT_SIZE)
     .with_text_color(palette::ID_TEXT_COLOR)
     .center()
     .background(palette::ID_BACKGROUND_COLOR);
 
-    let id_box = SizedBox::new(id_label).width(300.0).height(300.0).center();
+    let id_box = SizedBox::new(id_label)
+        .width(palette::ID_BOX_SIZE)
+        .height(palette::ID_BOX_SIZE);
+
+    let details = Either::new(
+        |data: &State, _| data.details_open,
+        Label::dynamic(move |data: &State, _| {
+            data.monitors
+                .iter()
+                .find(|monitor| monitor.id == id)
+                .map(Monitor::details_str)
+                .unwrap_or_default()
+        })
+        .with_text_size(24.0)
+        .with_text_color(palette::ID_TEXT_COLOR)
+        .with_text_alignment(TextAlignment::Center)
+        // the id box colors stay readable on every monitor background
+        .padding(20.0)
+        .background(palette::ID_BACKGROUND_COLOR)
+        .padding((0.0, 20.0, 0.0, 0.0)),
+        SizedBox::empty(),
+    );
+
+    let warnings_label = Label::dynamic(|data: &State, _| warnings_text(data))
+        .with_text_size(24.0)
+        .with_text_color(palette::WARNING_TEXT_COLOR)
+        .padding(20.0)
+        .background(palette::WARNING_BACKGROUND_COLOR);
+
+    let config_errors = Either::new(
+        |data: &State, _| data.config_errors.is_empty(),
+        SizedBox::empty(),
+        Label::dynamic(|data: &State, _| config_errors_text(data))
+            .with_text_size(24.0)
+            .with_text_color(palette::WARNING_TEXT_COLOR)
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .padding(20.0)
+            .background(palette::WARNING_BACKGROUND_COLOR)
+            .fix_width(900.0),
+    );
+
+    let warnings_box = Either::new(
+        |data: &State, _| data.warnings.is_empty(),
+        SizedBox::empty(),
+        warnings_label,
+    );
+
+    let hint = Either::new(
+        |data: &State, _| data.config.hint_dismissed,
+        SizedBox::empty(),
+        Label::dynamic(|data: &State, _| hint_text(data))
+            .with_text_size(20.0)
+            .padding(20.0)
+            .background(palette::PANEL_BACKGROUND_COLOR),
+    );
+
+    let overlay = Flex::column()
+        .with_child(id_box)
+        .with_child(details)
+        .with_spacer(40.0)
+        .with_child(config_errors)
+        .with_spacer(20.0)
+        .with_child(warnings_box)
+        .with_spacer(20.0)
+        .with_child(hint);
+
+    let content = Either::new(
+        move |data: &State, _| data.settings_monitor == Some(id),
+        settings::settings_builder().lens(State::settings),
+        Either::new(|data: &State, _| data.help_open, help_builder(), overlay),
+    )
+    .center();
+
+    // nothing to see with a single monitor
+    let minimap = Either::new(
+        |data: &State, _| data.monitors.len() > 1,
+        MiniMap::new(id).lens(State::monitors).padding(20.0),
+        SizedBox::empty(),
+    );
+
+    let window = SizedBox::new(content)
+        .expand()
+        .background(palette::MONITOR_BACKGROUND_COLOR)
+        .controller(Hoverable(id))
+        .controller(Geometry(id))
+        .on_click(move |_, data: &mut State, _| {
+            // the clicks in the settings panel reach this handler too
+            if data.settings_monitor.is_none() {
+                data.toggle(id);
+            }
+        });
 
     EnvScope::new(
-        move |env, data| {
+        move |env, data: &State| {
+            // the theme can change while the app is open
+            palette::add_to_env(env, &data.config.theme);
+
             if let Some(monitor) = data.monitors.iter().find(|monitor| monitor.id == id) {
                 if monitor.selected {
                     if monitor.id == data.hovered_id {
-                        env.set(palette::ID_BACKGROUND_COLOR, Color::WHITE);
+                        env.set(palette::ID_BACKGROUND_COLOR, env.get(palette::TEXT));
                         env.set(palette::ID_TEXT_COLOR, env.get(palette::DARK));
                     } else {
                         env.set(palette::ID_BACKGROUND_COLOR, env.get(palette::DARK));
-                        env.set(palette::ID_TEXT_COLOR, Color::WHITE);
+                        env.set(palette::ID_TEXT_COLOR, env.get(palette::TEXT));
                     }
 
                     env.set(palette::MONITOR_BACKGROUND_COLOR, env.get(palette::PRIMARY));
@@ -127,28 +505,97 @@ fn ui_builder(id: u32) -> impl Widget<State> {
                             env.get(palette::LIGHT_HOVERED),
                         );
                     } else {
-                        env.set(palette::MONITOR_BACKGROUND_COLOR, Color::BLACK);
+                        env.set(
+                            palette::MONITOR_BACKGROUND_COLOR,
+                            env.get(palette::BACKGROUND),
+                        );
                         env.set(palette::ID_BACKGROUND_COLOR, env.get(palette::LIGHT));
                     }
 
-                    env.set(palette::ID_TEXT_COLOR, Color::WHITE);
+                    env.set(palette::ID_TEXT_COLOR, env.get(palette::TEXT));
                 }
             }
         },
-        SizedBox::new(id_box)
-            .expand()
-            .background(palette::MONITOR_BACKGROUND_COLOR)
-            .controller(Hoverable(id))
-            .on_click(move |_, data, _| {
-                for i in 0..data.monitors.len() {
-                    if data.monitors[i].id == id {
-                        data.monitors[i].selected = !data.monitors[i].selected;
-                    }
-                }
-            }),
+        // the map covers the window, so its clicks don't toggle the monitor of the window
+        ZStack::new(window).with_aligned_child(minimap, UnitPoint::BOTTOM_RIGHT),
+    )
+}
+
+fn help_builder() -> impl Widget<State> {
+    let column = |show_keys: bool| {
+        Label::dynamic(move |data: &State, _| {
+            keys::help_entries(&keys::bindings(&data.config), &data.config)
+                .into_iter()
+                .map(|(names, description)| if show_keys { names } else { description })
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+        .with_text_size(20.0)
+    };
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new("Keyboard shortcuts").with_text_size(32.0))
+        .with_spacer(20.0)
+        .with_child(
+            Flex::row()
+                .cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_child(column(true))
+                .with_spacer(30.0)
+                .with_child(column(false)),
+        )
+        .with_spacer(20.0)
+        .with_child(Label::new("Click a monitor to select or unselect it.").with_text_size(20.0))
+        .padding(30.0)
+        .background(palette::PANEL_BACKGROUND_COLOR)
+}
+
+fn warnings_text(data: &State) -> String {
+    let bindings = keys::bindings(&data.config);
+
+    format!(
+        "{}\n\nPress {} again to connect anyway.",
+        data.warnings
+            .iter()
+            .cloned()
+            .collect::<Vec<String>>()
+            .join("\n"),
+        keys::key_names(&bindings, Action::Connect, &data.config)
     )
 }
 
+fn config_errors_text(data: &State) -> String {
+    let bindings = keys::bindings(&data.config);
+
+    format!(
+        "The configuration has errors:\n{}\n\nPress {} to fix them in the settings panel, or edit {}.",
+        data.config_errors.iter().cloned().collect::<Vec<String>>().join("\n"),
+        keys::key_names(&bindings, Action::OpenSettings, &data.config),
+        data.config.directories.config_path().display()
+    )
+}
+
+fn hint_text(data: &State) -> String {
+    let bindings = keys::bindings(&data.config);
+    let key_names = |action| keys::key_names(&bindings, action, &data.config);
+
+    let mut text = format!(
+        "Click the monitors to use, then press {} to connect.\nPress {} to see all the shortcuts, or {} to hide this hint.",
+        key_names(Action::Connect),
+        key_names(Action::ToggleHelp),
+        key_names(Action::DismissHint)
+    );
+
+    if !data.hint_error.is_empty() {
+        text.push_str(&format!(
+            "\n\nThe hint couldn't be hidden for good: {}",
+            data.hint_error
+        ));
+    }
+
+    text
+}
+
 struct Hoverable(u32);
 
 impl<W: Widget<State>> Controller<State, W> for Hoverable {
@@ -169,9 +616,34 @@ impl<W: Widget<State>> Controller<State, W> for Hoverable {
     }
 }
 
+struct Geometry(u32);
+
+impl<W: Widget<State>> Controller<State, W> for Geometry {
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &State,
+        data: &State,
+        env: &Env,
+    ) {
+        if old_data.config.fullscreen != data.config.fullscreen {
+            if let Some(monitor) = data.monitors.iter().find(|monitor| monitor.id == self.0) {
+                let (size, position) = window_geometry(&data.config, monitor);
+
+                ctx.window().set_size(size);
+                ctx.window().set_position(position);
+            }
+        }
+
+        child.update(ctx, old_data, data, env);
+    }
+}
+
 pub struct Delegate {
     main_window: WindowId,
     windows: Vec<u32>,
+    picking_host: bool,
 }
 
 impl Delegate {
@@ -179,6 +651,23 @@ impl Delegate {
         Self {
             main_window,
             windows,
+            picking_host: false,
+        }
+    }
+
+    pub fn with_host_picker(picker_window: WindowId, windows: Vec<u32>) -> Self {
+        Self {
+            main_window: picker_window,
+            windows,
+            picking_host: true,
+        }
+    }
+
+    fn open_windows(&self, ctx: &mut DelegateCtx, data: &State) {
+        for id in &self.windows {
+            if let Some(window) = window_builder(&data.config, *id, &data.monitors) {
+                ctx.new_window(window);
+            }
         }
     }
 }
@@ -193,25 +682,138 @@ impl AppDelegate<State> for Delegate {
         _env: &Env,
     ) -> Option<Event> {
         match &event {
-            Event::WindowConnected => {
-                if window_id == self.main_window {
-                    for id in &self.windows {
-                        let window = window_builder(&data.config, *id, &data.monitors).unwrap();
-                        ctx.new_window(window);
-                    }
-                }
+            Event::WindowConnected if window_id == self.main_window && !self.picking_host => {
+                self.open_windows(ctx, data);
+            }
+            // only Escape works until a host is picked
+            Event::KeyDown(event) if self.picking_host && event.key == KbKey::Escape => {
+                Application::global().quit()
+            }
+            Event::KeyDown(_) if self.picking_host => (),
+            Event::KeyDown(event)
+                if data.settings_monitor.is_some() && event.key == KbKey::Escape =>
+            {
+                data.close_settings()
             }
-            Event::KeyDown(event) => match event.key {
-                KbKey::Escape | KbKey::Backspace | KbKey::Delete => Application::global().quit(),
-                KbKey::Enter => {
-                    connection::start_rdc_session(&data.config, data.get_selected());
-                    Application::global().quit();
+            // the other keys go to the settings panel
+            Event::KeyDown(_) if data.settings_monitor.is_some() => (),
+            Event::KeyDown(event) => {
+                let bindings = keys::bindings(&data.config);
+
+                if let Some(action) =
+                    keys::find_action(&bindings, &event.key, event.mods, &data.config)
+                {
+                    perform(action, data);
                 }
-                _ => (),
-            },
+            }
             _ => (),
         }
 
         Some(event)
     }
+
+    fn command(
+        &mut self,
+        ctx: &mut DelegateCtx,
+        _target: Target,
+        cmd: &Command,
+        data: &mut State,
+        _env: &Env,
+    ) -> Handled {
+        if let Some(config) = cmd.get(CONFIG_RELOADED) {
+            data.set_config(config.clone());
+
+            return Handled::Yes;
+        }
+
+        if let Some(id) = cmd.get(TOGGLE_MONITOR) {
+            data.toggle(*id);
+            return Handled::Yes;
+        }
+
+        if cmd.is(SAVE_SETTINGS) {
+            data.save_settings();
+            return Handled::Yes;
+        }
+
+        if cmd.is(CLOSE_SETTINGS) {
+            data.close_settings();
+            return Handled::Yes;
+        }
+
+        if cmd.is(ADD_PRESET) {
+            let selected = data.get_selected();
+            data.settings.add_preset(&selected);
+            return Handled::Yes;
+        }
+
+        if cmd.is(HOST_PICKED) && self.picking_host {
+            self.picking_host = false;
+            self.open_windows(ctx, data);
+            ctx.submit_command(commands::CLOSE_WINDOW.to(self.main_window));
+
+            return Handled::Yes;
+        }
+
+        Handled::No
+    }
+}
+
+fn preview_message(diff: &str) -> String {
+    if diff.is_empty() {
+        String::from("The generated RDP file is identical to the base file.")
+    } else {
+        diff.to_string()
+    }
+}
+
+fn perform(action: Action, data: &mut State) {
+    match action {
+        Action::Connect => connect(data),
+        // Escape closes the help first
+        Action::Quit if data.help_open => data.help_open = false,
+        Action::Quit => Application::global().quit(),
+        Action::ToggleMonitor(id) => data.toggle(id),
+        Action::ToggleHovered => data.toggle(data.hovered_id),
+        Action::SelectAll => data.select_all(),
+        Action::SelectNone => data.select_none(),
+        Action::InvertSelection => data.invert_selection(),
+        Action::MoveHover(direction) => data.move_hover(direction),
+        Action::ApplyPreset(index) => data.apply_preset(index),
+        Action::OpenSettings => data.open_settings(),
+        Action::ToggleHelp => data.help_open = !data.help_open,
+        Action::ToggleDetails => data.details_open = !data.details_open,
+        Action::DismissHint => data.dismiss_hint(),
+    }
+}
+
+fn connect(data: &mut State) {
+    // the errors are displayed until they are fixed
+    if !data.config_errors.is_empty() {
+        return;
+    }
+
+    let warnings = data.selection_warnings();
+
+    // the first Enter only shows the warnings, the second one connects anyway
+    if data.warnings.is_empty() && !warnings.is_empty() {
+        data.warnings = warnings.iter().map(ToString::to_string).collect();
+        return;
+    }
+
+    let config = data.connection_config();
+
+    let result = if config.dry_run {
+        connection::preview_rdc_session(&config, &data.get_selected())
+            .map(|diff| platform::show_message(&preview_message(&diff)))
+    } else {
+        LastSelection::new(&data.monitors).save(&config.directories);
+        connection::start_rdc_session(&config, data.get_selected())
+    };
+
+    if let Err(err) = result {
+        platform::show_error(&err.to_string());
+    }
+
+    Application::global().quit();
 }
@@ -0,0 +1,279 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{io::Write, path::PathBuf};
+
+use clap::{Args, Parser, Subcommand};
+use serde_yaml::{Mapping, Value};
+
+use crate::{
+    config::Config,
+    connection,
+    diagnostic::Diagnostic,
+    error::{Error, Result},
+    layer::{Override, Source},
+    monitor::{MonitorProvider, Monitors, StaticMonitorProvider},
+    validation,
+};
+
+#[derive(Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    /// Use the monitors described in a YAML file instead of the actual ones
+    #[arg(long, value_name = "FILE", global = true)]
+    pub monitors_fixture: Option<PathBuf>,
+
+    /// Show how the base RDP file would be changed instead of starting the remote session
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Override a setting for this run, like `fullscreen=false` or `client=remmina`
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    pub overrides: Vec<Override>,
+
+    /// Connect to one of the saved hosts instead of picking it
+    #[arg(long, value_name = "NAME", global = true)]
+    pub host: Option<String>,
+
+    /// Start the remote session right away with a preset
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum Command {
+    /// List the active monitors
+    List,
+    /// Start the remote session on the given monitors
+    Connect {
+        #[command(flatten)]
+        selection: SelectionArgs,
+    },
+    /// Check whether the given monitors can be used together
+    Check {
+        #[command(flatten)]
+        selection: SelectionArgs,
+    },
+    /// Generate the RDP file for the given monitors without connecting
+    Generate {
+        #[command(flatten)]
+        selection: SelectionArgs,
+        /// Write the RDP file to this path instead of the standard output
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+pub enum ConfigCommand {
+    /// Check the configuration for problems that would prevent connecting
+    Check,
+    /// Print the effective configuration and where each setting comes from
+    Show,
+}
+
+#[derive(Args, Clone)]
+pub struct SelectionArgs {
+    /// Comma-separated IDs of the monitors to use
+    #[arg(long, value_delimiter = ',', required_unless_present = "preset")]
+    monitors: Vec<u32>,
+    /// Use the monitors and base RDP file of a preset
+    #[arg(long, value_name = "NAME", conflicts_with = "monitors")]
+    preset: Option<String>,
+}
+
+impl Cli {
+    pub fn monitors(&self) -> Result<Monitors> {
+        match &self.monitors_fixture {
+            Some(path) => StaticMonitorProvider::from_fixture(path)?.monitors(),
+            None => Monitors::enum_active(),
+        }
+    }
+
+    pub fn command(&self) -> Option<Command> {
+        match &self.preset {
+            Some(preset) => Some(Command::Connect {
+                selection: SelectionArgs {
+                    monitors: Vec::new(),
+                    preset: Some(preset.clone()),
+                },
+            }),
+            None => self.command.clone(),
+        }
+    }
+}
+
+impl SelectionArgs {
+    fn resolve(&self, config: &Config, monitors: &Monitors) -> Result<(Config, Vec<u32>)> {
+        let (config, ids) = match &self.preset {
+            Some(name) => {
+                let preset = config
+                    .preset(name)
+                    .ok_or_else(|| Error::UnknownPreset(name.clone()))?;
+
+                (config.for_preset(preset), preset.monitors.clone())
+            }
+            None => (config.clone(), self.monitors.clone()),
+        };
+
+        check_monitor_ids(monitors, &ids)?;
+
+        Ok((config, ids))
+    }
+}
+
+impl Command {
+    pub fn checks_config(&self) -> bool {
+        !matches!(self, Command::List | Command::Config { .. })
+    }
+
+    pub fn run(&self, config: &Config, monitors: &Monitors) -> Result<()> {
+        match self {
+            Command::List => {
+                for monitor in monitors.list() {
+                    println!("{}", monitor.info_str());
+                }
+            }
+            Command::Connect { selection } => {
+                let (config, ids) = selection.resolve(config, monitors)?;
+                print_warnings(monitors, &ids);
+
+                if config.dry_run {
+                    print!("{}", connection::preview_rdc_session(&config, &ids)?);
+                } else {
+                    connection::start_rdc_session(&config, ids)?;
+                }
+            }
+            Command::Check { selection } => {
+                let (_, ids) = selection.resolve(config, monitors)?;
+
+                let warning_count = print_warnings(monitors, &ids);
+
+                if warning_count > 0 {
+                    return Err(Error::SelectionWarnings(warning_count));
+                }
+
+                println!("The selected monitors can be used together.");
+            }
+            Command::Generate { selection, output } => {
+                let (config, ids) = selection.resolve(config, monitors)?;
+                print_warnings(monitors, &ids);
+
+                let rdp_file = connection::generate_rdp_file(&config, &ids)?;
+
+                if let Some(output) = output {
+                    rdp_file.write(output).map_err(|source| Error::WriteRdp {
+                        path: output.clone(),
+                        source,
+                    })?;
+                } else {
+                    // in the encoding of the base file, like with --output
+                    std::io::stdout()
+                        .write_all(&rdp_file.to_bytes())
+                        .map_err(Error::WriteOutput)?;
+                }
+            }
+            Command::Config { command } => command.run(config)?,
+        }
+
+        Ok(())
+    }
+}
+
+impl ConfigCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        match self {
+            ConfigCommand::Check => {
+                check_config(config, None)?;
+
+                println!("The configuration is valid.");
+            }
+            ConfigCommand::Show => print_config(config)?,
+        }
+
+        Ok(())
+    }
+}
+
+pub fn check_config(config: &Config, monitors: Option<&Monitors>) -> Result<()> {
+    let (errors, warnings): (Vec<Diagnostic>, Vec<Diagnostic>) = config
+        .validate(monitors)
+        .into_iter()
+        .partition(Diagnostic::is_error);
+
+    for warning in &warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::InvalidConfig(errors))
+    }
+}
+
+fn print_config(config: &Config) -> Result<()> {
+    let Value::Mapping(settings) = serde_yaml::to_value(config).map_err(Error::SerializeConfig)?
+    else {
+        return Ok(());
+    };
+
+    for (key, value) in settings {
+        let source = key
+            .as_str()
+            .and_then(|key| config.sources.get(key))
+            .unwrap_or(&Source::Default);
+
+        let mut setting = Mapping::new();
+        setting.insert(key, value);
+
+        println!("# {}", source);
+        print!(
+            "{}",
+            serde_yaml::to_string(&setting).map_err(Error::SerializeConfig)?
+        );
+    }
+
+    Ok(())
+}
+
+fn check_monitor_ids(monitors: &Monitors, ids: &[u32]) -> Result<()> {
+    match ids
+        .iter()
+        .find(|&&id| !monitors.list().iter().any(|monitor| monitor.id == id))
+    {
+        Some(&id) => Err(Error::UnknownMonitor(id)),
+        None => Ok(()),
+    }
+}
+
+fn print_warnings(monitors: &Monitors, ids: &[u32]) -> usize {
+    let list = monitors.list();
+    let warnings =
+        validation::validate_selection(list.iter().filter(|monitor| ids.contains(&monitor.id)));
+
+    for warning in &warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    warnings.len()
+}
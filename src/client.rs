@@ -0,0 +1,300 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+    process,
+};
+
+use druid::Data;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    monitor::join_ids,
+    rdp::RdpFile,
+};
+
+const MSTSC_PATH: &str = "C:\\Windows\\system32\\mstsc";
+
+#[derive(Serialize, Deserialize, Data, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientKind {
+    Mstsc,
+    Xfreerdp,
+    Wlfreerdp,
+    Remmina,
+}
+
+impl Default for ClientKind {
+    fn default() -> Self {
+        if cfg!(windows) {
+            ClientKind::Mstsc
+        } else {
+            ClientKind::Xfreerdp
+        }
+    }
+}
+
+impl ClientKind {
+    pub fn client(self) -> Box<dyn RdpClient> {
+        match self {
+            ClientKind::Mstsc => Box::new(Mstsc),
+            ClientKind::Xfreerdp => Box::new(FreeRdp::new("xfreerdp")),
+            ClientKind::Wlfreerdp => Box::new(FreeRdp::new("wlfreerdp")),
+            ClientKind::Remmina => Box::new(Remmina),
+        }
+    }
+}
+
+pub struct Session<'a> {
+    pub rdp_file: &'a RdpFile,
+    pub rdp_path: &'a Path,
+    pub selected_monitors: &'a [u32],
+    pub edit_connection: bool,
+}
+
+/// Kept apart from `std::process::Command` so that it can be inspected before anything is
+/// spawned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LaunchCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl LaunchCommand {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn spawn(&self) -> Result<()> {
+        // the client keeps running for the whole remote session, so it isn't waited for
+        let _session = process::Command::new(&self.program)
+            .args(&self.args)
+            .spawn()
+            .map_err(|source| Error::LaunchClient {
+                program: self.program.clone(),
+                source,
+            })?;
+
+        Ok(())
+    }
+}
+
+pub trait RdpClient {
+    fn command(&self, session: &Session) -> LaunchCommand;
+
+    /// A profile to write before launching, for the clients that can't open `.rdp` files.
+    fn profile(&self, _session: &Session) -> Option<(PathBuf, String)> {
+        None
+    }
+}
+
+pub struct Mstsc;
+
+impl RdpClient for Mstsc {
+    fn command(&self, session: &Session) -> LaunchCommand {
+        let mut command = LaunchCommand::new(MSTSC_PATH);
+
+        if session.edit_connection {
+            command = command.arg("/edit");
+        }
+
+        command.arg(session.rdp_path.to_string_lossy())
+    }
+}
+
+pub struct FreeRdp {
+    program: String,
+}
+
+impl FreeRdp {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+        }
+    }
+}
+
+impl RdpClient for FreeRdp {
+    fn command(&self, session: &Session) -> LaunchCommand {
+        let command = LaunchCommand::new(&self.program).arg(session.rdp_path.to_string_lossy());
+
+        let command = if session.selected_monitors.len() > 1 {
+            command.arg("/multimon")
+        } else {
+            command.arg("/f")
+        };
+
+        command.arg(format!("/monitors:{}", join_ids(session.selected_monitors)))
+    }
+}
+
+pub struct Remmina;
+
+impl RdpClient for Remmina {
+    fn command(&self, session: &Session) -> LaunchCommand {
+        LaunchCommand::new("remmina")
+            .arg("-c")
+            .arg(remmina_profile_path(session.rdp_path).to_string_lossy())
+    }
+
+    fn profile(&self, session: &Session) -> Option<(PathBuf, String)> {
+        let rdp_file = session.rdp_file;
+        let mut profile = String::from("[remmina]\nname=Instant Desktop\nprotocol=RDP\n");
+
+        let fields = [
+            ("server", "full address"),
+            ("username", "username"),
+            ("domain", "domain"),
+            ("gateway_server", "gatewayhostname"),
+        ];
+
+        for (remmina_key, rdp_key) in fields {
+            if let Some(value) = rdp_file.get_string(rdp_key) {
+                let _ = writeln!(profile, "{}={}", remmina_key, value);
+            }
+        }
+
+        let _ = writeln!(profile, "multimon=1");
+        let _ = writeln!(
+            profile,
+            "monitorids={}",
+            join_ids(session.selected_monitors)
+        );
+
+        Some((remmina_profile_path(session.rdp_path), profile))
+    }
+}
+
+fn remmina_profile_path(rdp_path: &Path) -> PathBuf {
+    rdp_path.with_extension("remmina")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rdp::RdpValue;
+
+    fn session<'a>(
+        rdp_file: &'a RdpFile,
+        selected_monitors: &'a [u32],
+        edit_connection: bool,
+    ) -> Session<'a> {
+        Session {
+            rdp_file,
+            rdp_path: Path::new("Instant Desktop/custom.rdp"),
+            selected_monitors,
+            edit_connection,
+        }
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn mstsc_opens_the_rdp_file() {
+        let rdp_file = RdpFile::new();
+        let command = Mstsc.command(&session(&rdp_file, &[0, 1], false));
+
+        assert_eq!(command.program, MSTSC_PATH);
+        assert_eq!(command.args, args(&["Instant Desktop/custom.rdp"]));
+        assert_eq!(Mstsc.profile(&session(&rdp_file, &[0, 1], false)), None);
+    }
+
+    #[test]
+    fn mstsc_can_edit_the_connection_first() {
+        let rdp_file = RdpFile::new();
+        let command = Mstsc.command(&session(&rdp_file, &[0, 1], true));
+
+        assert_eq!(command.args, args(&["/edit", "Instant Desktop/custom.rdp"]));
+    }
+
+    #[test]
+    fn freerdp_spans_several_monitors() {
+        let rdp_file = RdpFile::new();
+        let command = FreeRdp::new("xfreerdp").command(&session(&rdp_file, &[2, 0, 1], false));
+
+        assert_eq!(
+            command,
+            LaunchCommand::new("xfreerdp")
+                .arg("Instant Desktop/custom.rdp")
+                .arg("/multimon")
+                .arg("/monitors:2,0,1")
+        );
+    }
+
+    #[test]
+    fn freerdp_goes_fullscreen_on_a_single_monitor() {
+        let rdp_file = RdpFile::new();
+        let command = FreeRdp::new("wlfreerdp").command(&session(&rdp_file, &[1], true));
+
+        assert_eq!(command.program, "wlfreerdp");
+        assert_eq!(
+            command.args,
+            args(&["Instant Desktop/custom.rdp", "/f", "/monitors:1"])
+        );
+    }
+
+    #[test]
+    fn remmina_opens_a_profile_next_to_the_rdp_file() {
+        let rdp_file = RdpFile::new();
+        let command = Remmina.command(&session(&rdp_file, &[0], false));
+
+        assert_eq!(command.program, "remmina");
+        assert_eq!(command.args[0], "-c");
+        assert_eq!(
+            PathBuf::from(&command.args[1]),
+            Path::new("Instant Desktop/custom.remmina")
+        );
+    }
+
+    #[test]
+    fn remmina_profile_comes_from_the_rdp_file() {
+        let mut rdp_file = RdpFile::new();
+        rdp_file.set(
+            "full address",
+            RdpValue::String(String::from("host.example.com")),
+        );
+        rdp_file.set("username", RdpValue::String(String::from("jdoe")));
+        rdp_file.set("gatewayhostname", RdpValue::String(String::from("gateway")));
+
+        let (path, profile) = Remmina
+            .profile(&session(&rdp_file, &[0, 2], false))
+            .unwrap();
+
+        assert_eq!(path, Path::new("Instant Desktop/custom.remmina"));
+        assert_eq!(
+            profile,
+            "[remmina]\n\
+             name=Instant Desktop\n\
+             protocol=RDP\n\
+             server=host.example.com\n\
+             username=jdoe\n\
+             gateway_server=gateway\n\
+             multimon=1\n\
+             monitorids=0,2\n"
+        );
+    }
+}
@@ -12,92 +12,1092 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
 
 use directories::{ProjectDirs, UserDirs};
 use druid::Data;
 use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+use crate::{
+    client::ClientKind,
+    diagnostic::{Diagnostic, Segment, Severity},
+    error::{Error, Result},
+    keys::{self, KeyChords},
+    layer::{self, Override, Source, Sources},
+    migration::{self, CONFIG_VERSION},
+    monitor::Monitors,
+    rdp::{RdpFile, RdpValue},
+    theme::{self, Theme},
+};
 
 #[derive(Serialize, Deserialize, Data, Clone)]
 pub struct Config {
     #[serde(skip)]
     #[data(ignore)]
     pub directories: Directories,
+    #[serde(skip)]
+    #[data(ignore)]
+    pub sources: Sources,
+    pub version: u32,
+    #[serde(default)]
     #[data(same_fn = "PartialEq::eq")]
     pub base_config_path: PathBuf,
+    #[serde(default = "default_true")]
     pub fullscreen: bool,
+    #[serde(default = "default_true")]
     pub edit_connection: bool,
+    #[serde(default)]
+    pub client: ClientKind,
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub hint_dismissed: bool,
+    #[serde(default)]
+    pub show_details: bool,
+    #[serde(default)]
+    #[data(same_fn = "PartialEq::eq")]
+    pub presets: Vec<Preset>,
+    #[serde(default)]
+    #[data(same_fn = "PartialEq::eq")]
+    pub hosts: Vec<Host>,
+    #[serde(default)]
+    #[data(same_fn = "PartialEq::eq")]
+    pub theme: Theme,
+    #[serde(default)]
+    #[data(same_fn = "PartialEq::eq")]
+    pub keybindings: BTreeMap<String, KeyChords>,
+    #[serde(skip)]
+    #[data(same_fn = "PartialEq::eq")]
+    pub host: Option<Host>,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        let directories = Directories::default();
-
+impl Config {
+    pub fn new(directories: Directories) -> Self {
         let mut base_config_path = directories.document_dir();
         base_config_path.push("Default.rdp");
 
-        let config_dir = directories.project.config_dir().to_path_buf();
-        let data_dir = directories.project.data_dir().to_path_buf();
-        fs::create_dir_all(&config_dir).expect("failed to create config directory");
-        fs::create_dir_all(&data_dir).expect("failed to create data directory");
-
         Self {
             directories,
+            sources: Sources::new(),
+            version: CONFIG_VERSION,
             base_config_path,
             fullscreen: true,
             edit_connection: true,
+            client: ClientKind::default(),
+            dry_run: false,
+            hint_dismissed: false,
+            show_details: false,
+            presets: Vec::new(),
+            hosts: Vec::new(),
+            theme: Theme::default(),
+            keybindings: BTreeMap::new(),
+            host: None,
+        }
+    }
+
+    /// Leaves the defaults and the settings of the other layers out of the user's file.
+    pub fn save(&self) -> Result<()> {
+        let config_path = self.directories.config_path();
+
+        let mut mapping = read_layer(&config_path)?
+            .map(|layer| layer.mapping)
+            .unwrap_or_default();
+
+        if let Value::Mapping(settings) =
+            serde_yaml::to_value(self).map_err(Error::SerializeConfig)?
+        {
+            for (key, value) in settings {
+                match key.as_str().and_then(|key| self.sources.get(key)) {
+                    None | Some(Source::User(_)) => {
+                        mapping.insert(key, value);
+                    }
+                    Some(_) => (),
+                }
+            }
+        }
+
+        write_layer(&config_path, &mapping)
+    }
+
+    /// Marks the changed settings as the user's, so that `save` keeps them.
+    pub fn mark_changes(&mut self, original: &Config) -> Result<()> {
+        let (Value::Mapping(settings), Value::Mapping(original_settings)) = (
+            serde_yaml::to_value(&*self).map_err(Error::SerializeConfig)?,
+            serde_yaml::to_value(original).map_err(Error::SerializeConfig)?,
+        ) else {
+            return Ok(());
+        };
+
+        for (key, value) in settings {
+            if let Some(key) = key.as_str() {
+                if original_settings.get(key) != Some(&value) {
+                    self.sources.insert(
+                        key.to_string(),
+                        Source::User(self.directories.config_path()),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Merges, from the lowest priority, the defaults, the system-wide file, the user's file, the
+    /// `INSTANT_DESKTOP_*` variables among `vars` and the overrides.
+    pub fn load(
+        &mut self,
+        vars: impl Iterator<Item = (String, String)>,
+        overrides: &[Override],
+    ) -> Result<()> {
+        let mut sources = Sources::new();
+        let mut config = match serde_yaml::to_value(&*self).map_err(Error::SerializeConfig)? {
+            Value::Mapping(mapping) => mapping,
+            _ => Mapping::new(),
+        };
+
+        for key in config.keys().filter_map(Value::as_str) {
+            sources.insert(key.to_string(), Source::Default);
+        }
+
+        let system_config_path = self.directories.system_config_path();
+        let system_layer = read_layer(&system_config_path)?;
+        let has_system_layer = system_layer.is_some();
+
+        if let Some(ConfigLayer { mapping, .. }) = system_layer {
+            let source = Source::System(system_config_path);
+            layer::merge(&mut config, mapping, &source, &mut sources);
+        }
+
+        let config_path = self.directories.config_path();
+
+        match read_layer(&config_path)? {
+            Some(ConfigLayer {
+                mapping,
+                content,
+                version,
+            }) => {
+                if version < CONFIG_VERSION {
+                    let backup_path = self.directories.config_backup_path(version);
+
+                    fs::write(&backup_path, content).map_err(|source| Error::BackupConfig {
+                        path: backup_path,
+                        source,
+                    })?;
+
+                    write_layer(&config_path, &mapping)?;
+                }
+
+                layer::merge(
+                    &mut config,
+                    mapping,
+                    &Source::User(config_path),
+                    &mut sources,
+                );
+            }
+            // the defaults would hide the system-wide settings
+            None if has_system_layer => {
+                let mut mapping = Mapping::new();
+                mapping.insert(Value::from("version"), Value::from(CONFIG_VERSION));
+
+                write_layer(&config_path, &mapping)?;
+            }
+            None => self.save()?,
+        }
+
+        for (name, setting) in layer::env_overrides(vars) {
+            layer::apply(
+                &mut config,
+                &setting,
+                &Source::Environment(name),
+                &mut sources,
+            );
+        }
+
+        for setting in overrides {
+            layer::apply(&mut config, setting, &Source::CommandLine, &mut sources);
+        }
+
+        let config: Config =
+            serde_yaml::from_value(Value::Mapping(config)).map_err(Error::InvalidOverride)?;
+
+        *self = Config {
+            directories: self.directories.clone(),
+            sources,
+            ..config
+        };
+
+        Ok(())
+    }
+
+    /// The monitor IDs of the presets are only checked when the monitors are given.
+    pub fn validate(&self, monitors: Option<&Monitors>) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        // hosts with an address can connect without the base file
+        check_rdp_path(
+            &self.base_config_path,
+            vec![Segment::key("base_config_path")],
+            self.hosts.is_empty(),
+            &mut diagnostics,
+        );
+
+        let active_monitors = monitors.map(Monitors::list);
+
+        for (index, preset) in self.presets.iter().enumerate() {
+            let setting = |key: &str| {
+                vec![
+                    Segment::key("presets"),
+                    Segment::Index(index),
+                    Segment::key(key),
+                ]
+            };
+
+            if self.presets[..index]
+                .iter()
+                .any(|other| other.name == preset.name)
+            {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    setting("name"),
+                    "an earlier preset has the same name, so this one can't be used by name",
+                ));
+            }
+
+            if preset.monitors.is_empty() {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Error,
+                    setting("monitors"),
+                    "the preset doesn't select any monitor",
+                ));
+            }
+
+            for (id_index, id) in preset.monitors.iter().enumerate() {
+                if active_monitors
+                    .as_ref()
+                    .is_some_and(|active| !active.iter().any(|monitor| monitor.id == *id))
+                {
+                    let mut id_setting = setting("monitors");
+                    id_setting.push(Segment::Index(id_index));
+
+                    diagnostics.push(Diagnostic::new(
+                        Severity::Warning,
+                        id_setting,
+                        format!("no active monitor has the ID {}", id),
+                    ));
+                }
+            }
+
+            if let Some(base_config_path) = &preset.base_config_path {
+                check_rdp_path(
+                    base_config_path,
+                    setting("base_config_path"),
+                    true,
+                    &mut diagnostics,
+                );
+            }
+        }
+
+        for (index, host) in self.hosts.iter().enumerate() {
+            let setting = |key: &str| {
+                vec![
+                    Segment::key("hosts"),
+                    Segment::Index(index),
+                    Segment::key(key),
+                ]
+            };
+
+            if self.hosts[..index]
+                .iter()
+                .any(|other| other.name == host.name)
+            {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    setting("name"),
+                    "an earlier host has the same name, so this one can't be used by name",
+                ));
+            }
+
+            if let Some(base_config_path) = &host.base_config_path {
+                check_rdp_path(
+                    base_config_path,
+                    setting("base_config_path"),
+                    host.full_address.is_none(),
+                    &mut diagnostics,
+                );
+            }
+        }
+
+        for (name, hex) in self.theme.colors() {
+            if let Some(Err(message)) = hex.map(theme::parse_color) {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Error,
+                    vec![Segment::key("theme"), Segment::key(name)],
+                    message,
+                ));
+            }
+        }
+
+        for (name, size) in [
+            ("id_font_size", self.theme.id_font_size),
+            ("id_box_size", self.theme.id_box_size),
+        ] {
+            if size.is_some_and(|size| !size.is_finite() || size <= 0.0) {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Error,
+                    vec![Segment::key("theme"), Segment::key(name)],
+                    "the size must be a positive number",
+                ));
+            }
+        }
+
+        for error in keys::custom_bindings(&self.keybindings).1 {
+            let mut setting = vec![Segment::key("keybindings"), Segment::key(&error.action)];
+            setting.extend(error.index.map(Segment::Index));
+
+            diagnostics.push(Diagnostic::new(Severity::Error, setting, error.message));
+        }
+
+        // each setting is located in the file it was loaded from
+        let mut files = Vec::<(PathBuf, Option<String>)>::new();
+
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| {
+                let path = match diagnostic
+                    .setting
+                    .first()
+                    .and_then(|segment| match segment {
+                        Segment::Key(key) => self.sources.get(key),
+                        Segment::Index(_) => None,
+                    }) {
+                    Some(Source::System(path) | Source::User(path)) => path.clone(),
+                    _ => return diagnostic,
+                };
+
+                let source = match files.iter().find(|(file_path, _)| *file_path == path) {
+                    Some((_, source)) => source.clone(),
+                    None => {
+                        let source = fs::read_to_string(&path).ok();
+                        files.push((path, source.clone()));
+                        source
+                    }
+                };
+
+                diagnostic.locate(source.as_deref())
+            })
+            .collect()
+    }
+
+    pub fn preset(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    pub fn for_preset(&self, preset: &Preset) -> Config {
+        let mut config = self.clone();
+
+        // the base RDP file of the chosen host wins over the preset's
+        let host_has_base = self
+            .host
+            .as_ref()
+            .is_some_and(|host| host.base_config_path.is_some());
+
+        if let (Some(base_config_path), false) = (&preset.base_config_path, host_has_base) {
+            config.base_config_path = base_config_path.clone();
+        }
+
+        config
+    }
+
+    pub fn host(&self, name: &str) -> Option<&Host> {
+        self.hosts.iter().find(|host| host.name == name)
+    }
+
+    pub fn for_host(&self, host: &Host) -> Config {
+        let mut config = self.clone();
+
+        if let Some(base_config_path) = &host.base_config_path {
+            config.base_config_path = base_config_path.clone();
         }
+
+        config.host = Some(host.clone());
+
+        config
     }
 }
 
-impl Config {
-    pub fn save(&self) {
-        let config_string = serde_yaml::to_string(self).expect("failed to serialize");
-        fs::write(self.directories.config_path(), config_string)
-            .expect("failed to write config file");
+fn default_true() -> bool {
+    true
+}
+
+struct ConfigLayer {
+    mapping: Mapping,
+    content: String,
+    version: u32,
+}
+
+fn read_layer(path: &Path) -> Result<Option<ConfigLayer>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(source) => {
+            return Err(Error::ReadConfig {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+    };
+
+    let parse_error = |source| Error::ParseConfig {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let mut value = serde_yaml::from_str(&content).map_err(parse_error)?;
+    let version = migration::migrate(&mut value);
+
+    if version > CONFIG_VERSION {
+        return Err(Error::UnsupportedConfigVersion {
+            path: path.to_path_buf(),
+            version,
+        });
+    }
+
+    // parsing the text itself, when it wasn't migrated, keeps the location of the errors
+    if version == CONFIG_VERSION {
+        serde_yaml::from_str::<Config>(&content).map_err(parse_error)?;
+    } else {
+        serde_yaml::from_value::<Config>(value.clone()).map_err(parse_error)?;
+    }
+
+    let mapping = match value {
+        Value::Mapping(mapping) => mapping,
+        _ => Mapping::new(),
+    };
+
+    Ok(Some(ConfigLayer {
+        mapping,
+        content,
+        version,
+    }))
+}
+
+fn write_layer(path: &Path, mapping: &Mapping) -> Result<()> {
+    let content = serde_yaml::to_string(mapping).map_err(Error::SerializeConfig)?;
+
+    fs::write(path, content).map_err(|source| Error::WriteConfig {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// The file may not exist yet when it isn't `required`.
+fn check_rdp_path(
+    path: &Path,
+    setting: Vec<Segment>,
+    required: bool,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if path.is_relative() {
+        diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            setting,
+            format!("\"{}\" must be an absolute path", path.display()),
+        ));
+
+        return;
     }
 
-    pub fn load(&mut self) {
-        if let Ok(config_content) = fs::read_to_string(self.directories.config_path()) {
-            *self = serde_yaml::from_str(&config_content).expect("failed to deserialize");
-        } else {
-            self.save();
+    match RdpFile::read(path) {
+        Ok(rdp_file) => {
+            if let Some((number, text)) = rdp_file.invalid_lines().next() {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    setting,
+                    format!(
+                        "line {} of {} isn't a valid setting and will be kept as is: {}",
+                        number,
+                        path.display(),
+                        text
+                    ),
+                ));
+            }
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            let severity = if required {
+                Severity::Error
+            } else {
+                Severity::Warning
+            };
+
+            diagnostics.push(Diagnostic::new(
+                severity,
+                setting,
+                format!("{} doesn't exist", path.display()),
+            ));
         }
+        Err(err) => diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            setting,
+            format!("{} can't be read as an RDP file: {}", path.display(), err),
+        )),
     }
 }
 
-#[derive(Clone)]
-pub struct Directories {
-    project: ProjectDirs,
-    user: UserDirs,
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Preset {
+    pub name: String,
+    pub monitors: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_config_path: Option<PathBuf>,
 }
 
-impl Default for Directories {
-    fn default() -> Self {
-        let project = ProjectDirs::from("", "", "Instant-Desktop")
-            .expect("failed to get project directories");
-        let user = UserDirs::new().expect("failed to get user directories");
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Host {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_config_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gateway: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+impl Host {
+    pub fn apply(&self, rdp_file: &mut RdpFile) {
+        if let Some(full_address) = &self.full_address {
+            rdp_file.set("full address", RdpValue::String(full_address.clone()));
+        }
+
+        if let Some(gateway) = &self.gateway {
+            rdp_file.set("gatewayhostname", RdpValue::String(gateway.clone()));
+            rdp_file.set("gatewayusagemethod", RdpValue::Integer(1));
+        }
 
-        Self { project, user }
+        if let Some(username) = &self.username {
+            rdp_file.set("username", RdpValue::String(username.clone()));
+        }
     }
 }
 
+#[derive(Clone, Default)]
+pub struct Directories {
+    system_config_dir: PathBuf,
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+    document_dir: PathBuf,
+}
+
 impl Directories {
+    pub fn new() -> Result<Self> {
+        let project = ProjectDirs::from("", "", "Instant-Desktop").ok_or(Error::Directories)?;
+        let user = UserDirs::new().ok_or(Error::Directories)?;
+
+        let directories = Self {
+            system_config_dir: system_config_dir(),
+            config_dir: project.config_dir().to_path_buf(),
+            data_dir: project.data_dir().to_path_buf(),
+            document_dir: user
+                .document_dir()
+                .unwrap_or_else(|| user.home_dir())
+                .to_path_buf(),
+        };
+
+        for dir in [&directories.config_dir, &directories.data_dir] {
+            fs::create_dir_all(dir).map_err(|source| Error::CreateDirectory {
+                path: dir.clone(),
+                source,
+            })?;
+        }
+
+        Ok(directories)
+    }
+
     pub fn config_path(&self) -> PathBuf {
-        let mut config_path = self.project.config_dir().to_path_buf();
+        let mut config_path = self.config_dir.clone();
         config_path.push("config.yaml");
 
         config_path
     }
 
+    pub fn system_config_path(&self) -> PathBuf {
+        let mut system_config_path = self.system_config_dir.clone();
+        system_config_path.push("config.yaml");
+
+        system_config_path
+    }
+
+    pub fn config_backup_path(&self, version: u32) -> PathBuf {
+        let mut config_backup_path = self.config_dir.clone();
+        config_backup_path.push(format!("config.v{}.yaml.bak", version));
+
+        config_backup_path
+    }
+
     pub fn document_dir(&self) -> PathBuf {
-        self.user.document_dir().unwrap().to_path_buf()
+        self.document_dir.clone()
     }
 
     pub fn custom_rdp_path(&self) -> PathBuf {
-        let mut custom_rdp_path = self.project.data_dir().to_path_buf();
+        let mut custom_rdp_path = self.data_dir.clone();
         custom_rdp_path.push("custom.rdp");
 
         custom_rdp_path
     }
+
+    pub fn last_selection_path(&self) -> PathBuf {
+        let mut last_selection_path = self.data_dir.clone();
+        last_selection_path.push("last_selection.yaml");
+
+        last_selection_path
+    }
+}
+
+fn system_config_dir() -> PathBuf {
+    if cfg!(windows) {
+        let mut system_config_dir = env::var_os("ProgramData")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("C:\\ProgramData"));
+        system_config_dir.push("Instant-Desktop");
+
+        system_config_dir
+    } else {
+        PathBuf::from("/etc/instant-desktop")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{iter, process};
+
+    use super::*;
+    use crate::monitor::{Monitor, MonitorRect};
+
+    struct TestDirectories {
+        root: PathBuf,
+        directories: Directories,
+    }
+
+    impl TestDirectories {
+        fn new(name: &str) -> Self {
+            let root = env::temp_dir().join(format!("instant-desktop-{}-{}", name, process::id()));
+            let _ = fs::remove_dir_all(&root);
+
+            let directories = Directories {
+                system_config_dir: root.join("system"),
+                config_dir: root.join("config"),
+                data_dir: root.join("data"),
+                document_dir: root.join("documents"),
+            };
+
+            fs::create_dir_all(&directories.config_dir).unwrap();
+
+            Self { root, directories }
+        }
+
+        fn with_fixture(name: &str, fixture: &str) -> Self {
+            let test_directories = Self::new(name);
+            fs::copy(
+                fixture_path(fixture),
+                test_directories.directories.config_path(),
+            )
+            .unwrap();
+
+            test_directories
+        }
+
+        fn load(&self) -> Result<Config> {
+            let mut config = Config::new(self.directories.clone());
+            config.load(iter::empty(), &[])?;
+
+            Ok(config)
+        }
+    }
+
+    impl Drop for TestDirectories {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn fixture_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures/configs")
+            .join(name)
+    }
+
+    fn read_fixture(name: &str) -> ConfigLayer {
+        match read_layer(&fixture_path(name)) {
+            Ok(Some(layer)) => layer,
+            Ok(None) => panic!("{} doesn't exist", name),
+            Err(err) => panic!("{}: {}", name, err),
+        }
+    }
+
+    #[test]
+    fn reads_each_version() {
+        for (name, version) in [("v0.yaml", 0), ("v0_partial.yaml", 0), ("v1.yaml", 1)] {
+            let layer = read_fixture(name);
+
+            assert_eq!(layer.version, version, "{}", name);
+            assert_eq!(
+                layer.mapping.get("version"),
+                Some(&Value::from(CONFIG_VERSION)),
+                "{}",
+                name
+            );
+            assert_eq!(
+                layer.content,
+                fs::read_to_string(fixture_path(name)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn migrates_v0() {
+        let test_directories = TestDirectories::with_fixture("migrates-v0", "v0.yaml");
+        let config = test_directories.load().unwrap();
+
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(
+            config.base_config_path,
+            Path::new("C:\\Users\\user\\Documents\\Default.rdp")
+        );
+        assert!(config.fullscreen);
+        assert!(config.edit_connection);
+
+        // the settings added since then get their defaults
+        assert_eq!(config.client, ClientKind::default());
+        assert!(!config.dry_run);
+        assert!(!config.hint_dismissed);
+        assert!(config.presets.is_empty());
+        assert!(config.hosts.is_empty());
+        assert_eq!(config.theme, Theme::default());
+        assert!(config.keybindings.is_empty());
+    }
+
+    #[test]
+    fn migrates_a_partial_v0() {
+        let test_directories =
+            TestDirectories::with_fixture("migrates-partial-v0", "v0_partial.yaml");
+        let config = test_directories.load().unwrap();
+
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(!config.fullscreen);
+        assert_eq!(config.presets.len(), 1);
+        assert_eq!(config.presets[0].name, "left pair");
+        assert_eq!(config.presets[0].monitors, vec![1, 2]);
+
+        // the missing settings get their defaults
+        assert_eq!(
+            config.base_config_path,
+            test_directories
+                .directories
+                .document_dir()
+                .join("Default.rdp")
+        );
+        assert!(config.edit_connection);
+        assert_eq!(
+            config.sources.get("edit_connection"),
+            Some(&Source::Default)
+        );
+        assert_eq!(
+            config.sources.get("fullscreen"),
+            Some(&Source::User(test_directories.directories.config_path()))
+        );
+    }
+
+    #[test]
+    fn backs_up_older_versions() {
+        let test_directories = TestDirectories::with_fixture("backs-up-v0", "v0.yaml");
+        let directories = &test_directories.directories;
+        test_directories.load().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(directories.config_backup_path(0)).unwrap(),
+            fs::read_to_string(fixture_path("v0.yaml")).unwrap()
+        );
+
+        // the migrated file is saved and loads without another migration
+        let migrated = read_layer(&directories.config_path()).unwrap().unwrap();
+        assert_eq!(migrated.version, CONFIG_VERSION);
+        assert_eq!(migrated.mapping.get("fullscreen"), Some(&Value::from(true)));
+    }
+
+    #[test]
+    fn loads_v1_as_is() {
+        let test_directories = TestDirectories::with_fixture("loads-v1", "v1.yaml");
+        let directories = &test_directories.directories;
+        let config = test_directories.load().unwrap();
+
+        assert!(!directories.config_backup_path(1).exists());
+        assert_eq!(
+            fs::read_to_string(directories.config_path()).unwrap(),
+            fs::read_to_string(fixture_path("v1.yaml")).unwrap()
+        );
+
+        assert!(!config.edit_connection);
+        assert_eq!(config.client, ClientKind::Xfreerdp);
+        assert_eq!(config.hosts.len(), 1);
+        assert_eq!(
+            config.hosts[0].full_address.as_deref(),
+            Some("workstation.example.com")
+        );
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let test_directories = TestDirectories::new("rejects-newer");
+        let config_path = test_directories.directories.config_path();
+        let newer = format!("version: {}\nfullscreen: false\n", CONFIG_VERSION + 1);
+        fs::write(&config_path, &newer).unwrap();
+
+        assert!(matches!(
+            test_directories.load(),
+            Err(Error::UnsupportedConfigVersion { path, version })
+                if path == config_path && version == CONFIG_VERSION + 1
+        ));
+
+        // the file is kept for the newer version
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), newer);
+    }
+
+    #[test]
+    fn never_saves_command_line_settings() {
+        let test_directories = TestDirectories::with_fixture("command-line", "v1.yaml");
+        let mut config = Config::new(test_directories.directories.clone());
+        config
+            .load(iter::empty(), &["dry_run=true".parse().unwrap()])
+            .unwrap();
+
+        assert!(config.dry_run);
+        assert_eq!(config.sources.get("dry_run"), Some(&Source::CommandLine));
+
+        let original = config.clone();
+        config.hint_dismissed = true;
+        config.mark_changes(&original).unwrap();
+        config.save().unwrap();
+
+        let saved = read_layer(&test_directories.directories.config_path())
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.mapping.get("dry_run"), Some(&Value::from(false)));
+        assert_eq!(
+            saved.mapping.get("hint_dismissed"),
+            Some(&Value::from(true))
+        );
+    }
+
+    fn monitors() -> Monitors {
+        let mut monitors = Monitors::new();
+        monitors.add(Monitor::new(
+            1,
+            MonitorRect::new(0, 0, 1920, 1080),
+            MonitorRect::new(0, 0, 1920, 1040),
+            true,
+        ));
+        monitors.add(Monitor::new(
+            2,
+            MonitorRect::new(1920, 0, 3840, 1080),
+            MonitorRect::new(1920, 0, 3840, 1040),
+            false,
+        ));
+
+        monitors
+    }
+
+    fn find<'a>(diagnostics: &'a [Diagnostic], setting: &str) -> Option<&'a Diagnostic> {
+        diagnostics
+            .iter()
+            .find(|diagnostic| diagnostic.setting_str() == setting)
+    }
+
+    #[test]
+    fn validates_the_base_file() {
+        let test_directories = TestDirectories::new("validates-base-file");
+        let mut config = Config::new(test_directories.directories.clone());
+
+        let diagnostics = config.validate(Some(&monitors()));
+        assert!(find(&diagnostics, "base_config_path").is_some_and(Diagnostic::is_error));
+
+        config.base_config_path = PathBuf::from("Default.rdp");
+        let diagnostics = config.validate(Some(&monitors()));
+        let diagnostic = find(&diagnostics, "base_config_path").unwrap();
+        assert!(diagnostic.is_error());
+        assert!(diagnostic.message.contains("absolute path"));
+
+        // a host with an address doesn't need the base file
+        config.base_config_path = test_directories.root.join("Missing.rdp");
+        config.hosts.push(Host {
+            name: String::from("workstation"),
+            base_config_path: None,
+            full_address: Some(String::from("workstation.example.com")),
+            gateway: None,
+            username: None,
+        });
+        let diagnostics = config.validate(Some(&monitors()));
+        assert_eq!(
+            find(&diagnostics, "base_config_path").map(|diagnostic| diagnostic.severity),
+            Some(Severity::Warning)
+        );
+
+        config.base_config_path = test_directories.root.join("Default.rdp");
+        fs::write(&config.base_config_path, "full address:s:host\r\n").unwrap();
+        assert!(find(&config.validate(Some(&monitors())), "base_config_path").is_none());
+    }
+
+    #[test]
+    fn validates_the_presets() {
+        let test_directories = TestDirectories::new("validates-presets");
+        let mut config = Config::new(test_directories.directories.clone());
+        config.presets = vec![
+            Preset {
+                name: String::from("left"),
+                monitors: Vec::new(),
+                base_config_path: None,
+            },
+            Preset {
+                name: String::from("left"),
+                monitors: vec![1, 5],
+                base_config_path: Some(PathBuf::from("left.rdp")),
+            },
+        ];
+
+        let diagnostics = config.validate(Some(&monitors()));
+
+        assert!(find(&diagnostics, "presets[0].monitors").is_some_and(Diagnostic::is_error));
+        assert!(find(&diagnostics, "presets[0].name").is_none());
+        assert_eq!(
+            find(&diagnostics, "presets[1].name").map(|diagnostic| diagnostic.severity),
+            Some(Severity::Warning)
+        );
+        assert!(find(&diagnostics, "presets[1].monitors[0]").is_none());
+        assert_eq!(
+            find(&diagnostics, "presets[1].monitors[1]").map(|diagnostic| diagnostic.severity),
+            Some(Severity::Warning)
+        );
+        assert!(find(&diagnostics, "presets[1].base_config_path").is_some_and(Diagnostic::is_error));
+
+        // the IDs can't be checked without the monitors
+        assert!(find(&config.validate(None), "presets[1].monitors[1]").is_none());
+    }
+
+    #[test]
+    fn validates_the_theme_and_key_bindings() {
+        let test_directories = TestDirectories::new("validates-theme");
+        let mut config = Config::new(test_directories.directories.clone());
+        config.theme = Theme {
+            primary: Some(String::from("yellow")),
+            text: Some(String::from("#fff")),
+            id_font_size: Some(0.0),
+            ..Default::default()
+        };
+        config.keybindings = BTreeMap::from([
+            (
+                String::from("connect"),
+                KeyChords::One(String::from("Ctrl+Nothing")),
+            ),
+            (
+                String::from("help"),
+                KeyChords::Many(vec![String::from("F1"), String::from("Hyper+H")]),
+            ),
+            (String::from("jump"), KeyChords::One(String::from("J"))),
+        ]);
+
+        let diagnostics = config.validate(Some(&monitors()));
+
+        for setting in [
+            "theme.primary",
+            "theme.id_font_size",
+            "keybindings.connect",
+            "keybindings.help[1]",
+            "keybindings.jump",
+        ] {
+            assert!(
+                find(&diagnostics, setting).is_some_and(Diagnostic::is_error),
+                "{}",
+                setting
+            );
+        }
+
+        assert!(find(&diagnostics, "theme.text").is_none());
+        assert!(find(&diagnostics, "theme.id_box_size").is_none());
+        assert!(find(&diagnostics, "keybindings.help[0]").is_none());
+    }
+
+    #[test]
+    fn locates_the_settings_in_their_file() {
+        let test_directories = TestDirectories::new("locates-settings");
+        fs::write(
+            test_directories.directories.config_path(),
+            format!(
+                "version: {}\npresets:\n  - name: empty\n    monitors: []\n",
+                CONFIG_VERSION
+            ),
+        )
+        .unwrap();
+        let config = test_directories.load().unwrap();
+
+        let diagnostics = config.validate(Some(&monitors()));
+
+        assert_eq!(
+            find(&diagnostics, "presets[0].monitors").and_then(|diagnostic| diagnostic.location),
+            Some((4, 5))
+        );
+
+        // the default settings aren't in any file
+        assert_eq!(
+            find(&diagnostics, "base_config_path").map(|diagnostic| diagnostic.location),
+            Some(None)
+        );
+    }
+
+    #[test]
+    fn applies_the_environment_below_the_command_line() {
+        let test_directories = TestDirectories::with_fixture("environment", "v1.yaml");
+        let mut config = Config::new(test_directories.directories.clone());
+        let vars = [
+            ("INSTANT_DESKTOP_FULLSCREEN", "false"),
+            ("INSTANT_DESKTOP_EDIT_CONNECTION", "true"),
+            ("OTHER_FULLSCREEN", "true"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+        config
+            .load(
+                vars.into_iter(),
+                &["edit_connection=false".parse().unwrap()],
+            )
+            .unwrap();
+
+        assert!(!config.fullscreen);
+        assert_eq!(
+            config.sources.get("fullscreen"),
+            Some(&Source::Environment(String::from(
+                "INSTANT_DESKTOP_FULLSCREEN"
+            )))
+        );
+        assert!(!config.edit_connection);
+        assert_eq!(
+            config.sources.get("edit_connection"),
+            Some(&Source::CommandLine)
+        );
+    }
+
+    #[test]
+    fn creates_a_missing_file() {
+        let test_directories = TestDirectories::new("creates-missing");
+        let config = test_directories.load().unwrap();
+
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(read_layer(&test_directories.directories.config_path())
+            .unwrap()
+            .is_some());
+    }
 }
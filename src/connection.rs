@@ -12,71 +12,97 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use lazy_regex::{regex, regex_replace_all};
-use std::{fs, process::Command};
+use std::{fs, io::ErrorKind};
 
-use crate::config::Config;
+use crate::{
+    client::Session,
+    config::Config,
+    diff,
+    error::{Error, Result},
+    monitor::join_ids,
+    rdp::{RdpFile, RdpValue},
+};
 
-pub fn start_rdc_session(config: &Config, selected_monitors: Vec<u32>) {
+pub fn start_rdc_session(config: &Config, selected_monitors: Vec<u32>) -> Result<()> {
+    let rdp_file = generate_rdp_file(config, &selected_monitors)?;
+
+    // write custom file
+    let custom_rdp_path = config.directories.custom_rdp_path();
+
+    rdp_file
+        .write(&custom_rdp_path)
+        .map_err(|source| Error::WriteRdp {
+            path: custom_rdp_path.clone(),
+            source,
+        })?;
+
+    // start remote desktop connection
+    let client = config.client.client();
+    let session = Session {
+        rdp_file: &rdp_file,
+        rdp_path: &custom_rdp_path,
+        selected_monitors: &selected_monitors,
+        edit_connection: config.edit_connection,
+    };
+
+    if let Some((profile_path, profile)) = client.profile(&session) {
+        fs::write(&profile_path, profile).map_err(|source| Error::WriteProfile {
+            path: profile_path,
+            source,
+        })?;
+    }
+
+    client.command(&session).spawn()
+}
+
+pub fn preview_rdc_session(config: &Config, selected_monitors: &[u32]) -> Result<String> {
+    let base_rdp_file = read_base_rdp_file(config)?;
+    let rdp_file = generate_rdp_file(config, selected_monitors)?;
+
+    Ok(diff::unified_diff(
+        &base_rdp_file.to_string(),
+        &rdp_file.to_string(),
+        &config.base_config_path.display().to_string(),
+        &config.directories.custom_rdp_path().display().to_string(),
+    ))
+}
+
+pub fn generate_rdp_file(config: &Config, selected_monitors: &[u32]) -> Result<RdpFile> {
     // read base file
-    let mut rdp_config =
-        fs::read_to_string(&config.base_config_path).expect("failed to read base config file");
+    let mut rdp_file = read_base_rdp_file(config)?;
 
-    // use_multimon parameter
-    let use_multimon_r = regex!(r#"^(use\smultimon:i:).*$"#im);
-
-    if use_multimon_r.is_match(&rdp_config) {
-        rdp_config = regex_replace_all!(
-            r#"^(use\smultimon:i:).*$"#im,
-            &rdp_config,
-            |_, key| format!("{}1", key),
-        )
-        .to_string();
-    } else {
-        rdp_config.push_str("\nuse multimon:i:1");
+    // host parameters
+    if let Some(host) = &config.host {
+        host.apply(&mut rdp_file);
     }
 
+    // use_multimon parameter
+    rdp_file.set("use multimon", RdpValue::Integer(1));
+
     // selectedmonitors parameter
-    let selected_value = selected_monitors
-        .iter()
-        .map(|id| id.to_string())
-        .collect::<Vec<String>>()
-        .join(",");
-
-    let selectedmonitors_r = regex!(r#"^(selectedmonitors:s:).*$"#im);
-
-    if selectedmonitors_r.is_match(&rdp_config) {
-        rdp_config = regex_replace_all!(
-            r#"^(selectedmonitors:s:).*$"#im,
-            &rdp_config,
-            |_, key| format!("{}{}", key, selected_value),
-        )
-        .to_string();
-    } else {
-        rdp_config.push_str(&format!("\nselectedmonitors:s:{}", selected_value));
-    }
+    rdp_file.set(
+        "selectedmonitors",
+        RdpValue::String(join_ids(selected_monitors)),
+    );
 
-    // write custom file
-    let custom_rdp_path = config.directories.custom_rdp_path();
+    Ok(rdp_file)
+}
 
-    if fs::write(&custom_rdp_path, rdp_config).is_ok() {
-        // start remote desktop connection
-        let mut mstsc_args = if config.edit_connection {
-            vec!["/edit"]
-        } else {
-            Vec::new()
-        };
-
-        mstsc_args.push(custom_rdp_path.to_str().unwrap());
-
-        Command::new("C:\\Windows\\system32\\mstsc")
-            .args(mstsc_args)
-            .spawn()
-            .expect("failed to execute process");
-    } else {
-        panic!(
-            "failed to write custom config file: {}",
-            custom_rdp_path.to_str().unwrap()
-        );
+fn read_base_rdp_file(config: &Config) -> Result<RdpFile> {
+    match RdpFile::read(&config.base_config_path) {
+        // a host with its address doesn't need a base file
+        Err(err)
+            if err.kind() == ErrorKind::NotFound
+                && config
+                    .host
+                    .as_ref()
+                    .is_some_and(|host| host.full_address.is_some()) =>
+        {
+            Ok(RdpFile::new())
+        }
+        result => result.map_err(|source| Error::ReadRdp {
+            path: config.base_config_path.clone(),
+            source,
+        }),
     }
 }
@@ -0,0 +1,345 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl Segment {
+    pub fn key(key: impl Into<String>) -> Self {
+        Segment::Key(key.into())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub setting: Vec<Segment>,
+    pub message: String,
+    /// The 1-based line and column of the setting.
+    pub location: Option<(usize, usize)>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, setting: Vec<Segment>, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            setting,
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    pub fn locate(mut self, source: Option<&str>) -> Self {
+        self.location = source.and_then(|source| locate(source, &self.setting));
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    pub fn setting_str(&self) -> String {
+        let mut setting = String::new();
+
+        for segment in &self.setting {
+            match segment {
+                Segment::Key(key) if setting.is_empty() => setting.push_str(key),
+                Segment::Key(key) => {
+                    setting.push('.');
+                    setting.push_str(key);
+                }
+                Segment::Index(index) => setting.push_str(&format!("[{}]", index)),
+            }
+        }
+
+        setting
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(
+                f,
+                "{} (line {}, column {}): {}",
+                self.setting_str(),
+                line,
+                column,
+                self.message
+            ),
+            None => write!(f, "{}: {}", self.setting_str(), self.message),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Token<'a> {
+    Item,
+    Key(&'a str),
+}
+
+struct Entry<'a> {
+    line: usize,
+    column: usize,
+    token: Token<'a>,
+}
+
+/// Finds the 1-based line and column of a setting in block-style YAML, falling back to its
+/// closest parent that could be found.
+pub fn locate(source: &str, setting: &[Segment]) -> Option<(usize, usize)> {
+    let entries = entries(source);
+
+    let mut found = None;
+    let mut start = 0;
+    let mut end = entries.len();
+
+    for segment in setting {
+        let region = &entries[start..end];
+        let Some(first) = region.first() else {
+            break;
+        };
+
+        let position = match segment {
+            Segment::Key(key) => region.iter().position(|entry| {
+                entry.column == first.column
+                    && matches!(entry.token, Token::Key(name) if name == key)
+            }),
+            Segment::Index(index) => region
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| {
+                    entry.column == first.column && matches!(entry.token, Token::Item)
+                })
+                .nth(*index)
+                .map(|(position, _)| position),
+        };
+
+        let Some(position) = position else {
+            break;
+        };
+
+        let index = start + position;
+        let entry = &entries[index];
+        found = Some((entry.line + 1, entry.column + 1));
+
+        // the children of an entry are the following, more indented entries, and for a key also
+        // the sequence items written at its own indentation
+        start = index + 1;
+        end = start
+            + entries[start..]
+                .iter()
+                .take_while(|child| {
+                    child.column > entry.column
+                        || (matches!(entry.token, Token::Key(_))
+                            && matches!(child.token, Token::Item)
+                            && child.column == entry.column)
+                })
+                .count();
+    }
+
+    found
+}
+
+fn entries(source: &str) -> Vec<Entry<'_>> {
+    let mut entries = Vec::new();
+
+    for (line, text) in source.lines().enumerate() {
+        let mut rest = text.trim_start();
+        let mut column = text.len() - rest.len();
+
+        if rest.is_empty() || rest.starts_with('#') || rest.starts_with("---") {
+            continue;
+        }
+
+        while let Some(item) = rest
+            .strip_prefix('-')
+            .filter(|item| item.is_empty() || item.starts_with(char::is_whitespace))
+        {
+            entries.push(Entry {
+                line,
+                column,
+                token: Token::Item,
+            });
+
+            let trimmed = item.trim_start();
+            column += rest.len() - trimmed.len();
+            rest = trimmed;
+        }
+
+        if let Some(key) = mapping_key(rest) {
+            entries.push(Entry {
+                line,
+                column,
+                token: Token::Key(key),
+            });
+        }
+    }
+
+    entries
+}
+
+fn mapping_key(text: &str) -> Option<&str> {
+    // a quoted key can contain colons
+    let (key, value) = match text.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let end = text[1..].find(quote)? + 1;
+            (
+                &text[1..end],
+                text[end + 1..].trim_start().strip_prefix(':')?,
+            )
+        }
+        _ => {
+            let (index, _) = text.match_indices(':').find(|(index, _)| {
+                text[index + 1..].is_empty() || text[index + 1..].starts_with(char::is_whitespace)
+            })?;
+
+            (text[..index].trim(), &text[index + 1..])
+        }
+    };
+
+    if !value.is_empty() && !value.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRESETS: &str = "\
+# presets
+presets:
+  - name: left pair
+    monitors: [1, 2]
+  - name: right pair
+    monitors:
+      - 2
+      - 3
+fullscreen: true
+";
+
+    fn setting(path: &[&str]) -> Vec<Segment> {
+        path.iter()
+            .map(|segment| match segment.parse() {
+                Ok(index) => Segment::Index(index),
+                Err(_) => Segment::key(*segment),
+            })
+            .collect()
+    }
+
+    fn locate_in(source: &str, path: &[&str]) -> Option<(usize, usize)> {
+        locate(source, &setting(path))
+    }
+
+    #[test]
+    fn locates_top_level_keys() {
+        let source = "---\nbase_config_path: C:\\Default.rdp\nfullscreen: true\n";
+
+        assert_eq!(locate_in(source, &["base_config_path"]), Some((2, 1)));
+        assert_eq!(locate_in(source, &["fullscreen"]), Some((3, 1)));
+        assert_eq!(locate_in(PRESETS, &["fullscreen"]), Some((9, 1)));
+    }
+
+    #[test]
+    fn locates_nested_preset_keys() {
+        assert_eq!(locate_in(PRESETS, &["presets", "0", "name"]), Some((3, 5)));
+        assert_eq!(locate_in(PRESETS, &["presets", "1", "name"]), Some((5, 5)));
+        assert_eq!(
+            locate_in(PRESETS, &["presets", "1", "monitors"]),
+            Some((6, 5))
+        );
+    }
+
+    #[test]
+    fn locates_sequence_items() {
+        assert_eq!(locate_in(PRESETS, &["presets", "1"]), Some((5, 3)));
+        assert_eq!(
+            locate_in(PRESETS, &["presets", "1", "monitors", "1"]),
+            Some((8, 7))
+        );
+
+        // the items of a sequence can be written at the indentation of its key
+        let source = "presets:\n- name: left pair\n  monitors:\n  - 1\n  - 2\n";
+        assert_eq!(
+            locate_in(source, &["presets", "0", "monitors", "1"]),
+            Some((5, 3))
+        );
+    }
+
+    #[test]
+    fn locates_quoted_keys() {
+        let source = "\"fullscreen\": true\n'hosts':\n  - \"name:with colon\": x\n";
+
+        assert_eq!(locate_in(source, &["fullscreen"]), Some((1, 1)));
+        assert_eq!(locate_in(source, &["hosts", "0"]), Some((3, 3)));
+        assert_eq!(
+            locate_in(source, &["hosts", "0", "name:with colon"]),
+            Some((3, 5))
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_parent() {
+        // the inline list has no entries of its own
+        assert_eq!(
+            locate_in(PRESETS, &["presets", "0", "monitors", "1"]),
+            Some((4, 5))
+        );
+        assert_eq!(
+            locate_in(PRESETS, &["presets", "1", "base_config_path"]),
+            Some((5, 3))
+        );
+        assert_eq!(locate_in(PRESETS, &["presets", "2", "name"]), Some((2, 1)));
+        assert_eq!(locate_in(PRESETS, &["hosts", "0"]), None);
+    }
+
+    #[test]
+    fn skips_values_that_look_like_keys() {
+        let source = "base_config_path: C:\\Default.rdp\nhosts:\n  - http://example.com\n";
+
+        assert_eq!(locate_in(source, &["hosts", "0"]), Some((3, 3)));
+        assert_eq!(locate_in(source, &["hosts", "0", "http"]), Some((3, 3)));
+    }
+
+    #[test]
+    fn displays_the_setting_and_location() {
+        let diagnostic = Diagnostic::new(
+            Severity::Error,
+            setting(&["presets", "1", "monitors"]),
+            "the preset doesn't select any monitor",
+        );
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "presets[1].monitors: the preset doesn't select any monitor"
+        );
+        assert_eq!(
+            diagnostic.locate(Some(PRESETS)).to_string(),
+            "presets[1].monitors (line 6, column 5): the preset doesn't select any monitor"
+        );
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Clone, Copy)]
+enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    // the lines keep their endings, so that a CRLF or final newline change shows up
+    let old_lines = old.split_inclusive('\n').collect::<Vec<&str>>();
+    let new_lines = new.split_inclusive('\n').collect::<Vec<&str>>();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    // group the changes with their context, merging the groups that overlap
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+
+    for (index, op) in ops.iter().enumerate() {
+        if matches!(op, Op::Equal(_)) {
+            continue;
+        }
+
+        let start = index.saturating_sub(CONTEXT_LINES);
+        let end = (index + CONTEXT_LINES + 1).min(ops.len());
+
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut output = format!("--- {}\n+++ {}\n", old_name, new_name);
+
+    for (start, end) in hunks {
+        let old_start = ops[..start]
+            .iter()
+            .filter(|op| !matches!(op, Op::Insert(_)))
+            .count();
+        let new_start = ops[..start]
+            .iter()
+            .filter(|op| !matches!(op, Op::Delete(_)))
+            .count();
+        let old_len = ops[start..end]
+            .iter()
+            .filter(|op| !matches!(op, Op::Insert(_)))
+            .count();
+        let new_len = ops[start..end]
+            .iter()
+            .filter(|op| !matches!(op, Op::Delete(_)))
+            .count();
+
+        output.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + usize::from(old_len > 0),
+            old_len,
+            new_start + usize::from(new_len > 0),
+            new_len
+        ));
+
+        for op in &ops[start..end] {
+            let (prefix, line) = match op {
+                Op::Equal(line) => (' ', line),
+                Op::Delete(line) => ('-', line),
+                Op::Insert(line) => ('+', line),
+            };
+
+            output.push(prefix);
+            output.push_str(line);
+
+            if !line.ends_with('\n') {
+                output.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    output
+}
+
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    let mut lengths = vec![vec![0; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push(Op::Equal(old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            ops.push(Op::Delete(old[i]));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(new[j]));
+            j += 1;
+        }
+    }
+
+    ops.extend(old[i..].iter().map(|line| Op::Delete(line)));
+    ops.extend(new[j..].iter().map(|line| Op::Insert(line)));
+
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(old: &str, new: &str) -> String {
+        unified_diff(old, new, "old", "new")
+    }
+
+    fn headers(diff: &str) -> Vec<&str> {
+        diff.lines().filter(|line| line.starts_with("@@")).collect()
+    }
+
+    fn numbered_lines(count: usize) -> Vec<String> {
+        (1..=count).map(|number| format!("{}\n", number)).collect()
+    }
+
+    #[test]
+    fn returns_nothing_for_the_same_texts() {
+        assert_eq!(diff("", ""), "");
+        assert_eq!(diff("a\nb\n", "a\nb\n"), "");
+    }
+
+    #[test]
+    fn inserts_into_an_empty_file() {
+        assert_eq!(
+            diff("", "a\nb\n"),
+            "--- old\n+++ new\n@@ -0,0 +1,2 @@\n+a\n+b\n"
+        );
+    }
+
+    #[test]
+    fn deletes_every_line() {
+        assert_eq!(
+            diff("a\nb\n", ""),
+            "--- old\n+++ new\n@@ -1,2 +0,0 @@\n-a\n-b\n"
+        );
+    }
+
+    #[test]
+    fn keeps_the_context_around_changes() {
+        assert_eq!(
+            diff("1\n2\n3\n4\n5\n6\n", "1\n2\n3\nfour\n5\n6\n"),
+            "--- old\n+++ new\n@@ -1,6 +1,6 @@\n 1\n 2\n 3\n-4\n+four\n 5\n 6\n"
+        );
+    }
+
+    #[test]
+    fn merges_the_hunks_within_the_context() {
+        let old = numbered_lines(10);
+        let mut new = old.clone();
+        new[1] = String::from("two\n");
+        new[7] = String::from("eight\n");
+
+        assert_eq!(
+            headers(&diff(&old.concat(), &new.concat())),
+            ["@@ -1,10 +1,10 @@"]
+        );
+    }
+
+    #[test]
+    fn splits_the_hunks_beyond_the_context() {
+        let old = numbered_lines(20);
+        let mut new = old.clone();
+        new[1] = String::from("two\n");
+        new[11] = String::from("twelve\n");
+
+        assert_eq!(
+            headers(&diff(&old.concat(), &new.concat())),
+            ["@@ -1,5 +1,5 @@", "@@ -9,7 +9,7 @@"]
+        );
+    }
+
+    #[test]
+    fn marks_a_missing_final_newline() {
+        assert_eq!(
+            diff("a\nb\n", "a\nb"),
+            "--- old\n+++ new\n@@ -1,2 +1,2 @@\n a\n-b\n+b\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn reports_line_ending_changes() {
+        assert_eq!(
+            diff("a\r\nb\n", "a\nb\n"),
+            "--- old\n+++ new\n@@ -1,2 +1,2 @@\n-a\r\n+a\n b\n"
+        );
+    }
+}
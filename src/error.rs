@@ -0,0 +1,217 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt, io, path::PathBuf};
+
+use druid::PlatformError;
+
+use crate::diagnostic::Diagnostic;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    Directories,
+    CreateDirectory {
+        path: PathBuf,
+        source: io::Error,
+    },
+    ReadConfig {
+        path: PathBuf,
+        source: io::Error,
+    },
+    ParseConfig {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
+    UnsupportedConfigVersion {
+        path: PathBuf,
+        version: u32,
+    },
+    BackupConfig {
+        path: PathBuf,
+        source: io::Error,
+    },
+    InvalidConfig(Vec<Diagnostic>),
+    InvalidOverride(serde_yaml::Error),
+    SerializeConfig(serde_yaml::Error),
+    WriteConfig {
+        path: PathBuf,
+        source: io::Error,
+    },
+    ReadRdp {
+        path: PathBuf,
+        source: io::Error,
+    },
+    WriteRdp {
+        path: PathBuf,
+        source: io::Error,
+    },
+    WriteProfile {
+        path: PathBuf,
+        source: io::Error,
+    },
+    WriteOutput(io::Error),
+    LaunchClient {
+        program: String,
+        source: io::Error,
+    },
+    EnumMonitors(Box<dyn std::error::Error + Send + Sync>),
+    NoMonitors,
+    ReadFixture {
+        path: PathBuf,
+        source: io::Error,
+    },
+    ParseFixture {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
+    UnknownMonitor(u32),
+    UnknownPreset(String),
+    UnknownHost(String),
+    SelectionWarnings(usize),
+    Launch(PlatformError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Directories => write!(f, "failed to find the user's directories"),
+            Error::CreateDirectory { path, source } => {
+                write!(f, "failed to create {}: {}", path.display(), source)
+            }
+            Error::ReadConfig { path, source } => {
+                write!(f, "failed to read {}: {}", path.display(), source)
+            }
+            Error::ParseConfig { path, source } => {
+                write!(f, "invalid configuration in {}: {}", path.display(), source)
+            }
+            Error::UnsupportedConfigVersion { path, version } => {
+                write!(
+                    f,
+                    "{} was written by a newer version of Instant Desktop (configuration version {})",
+                    path.display(),
+                    version
+                )
+            }
+            Error::BackupConfig { path, source } => {
+                write!(
+                    f,
+                    "failed to back up the configuration to {}: {}",
+                    path.display(),
+                    source
+                )
+            }
+            Error::InvalidConfig(diagnostics) => {
+                write!(f, "the configuration has {} error(s):", diagnostics.len())?;
+
+                for diagnostic in diagnostics {
+                    write!(f, "\n  {}", diagnostic)?;
+                }
+
+                Ok(())
+            }
+            Error::InvalidOverride(source) => {
+                write!(f, "invalid setting in the environment or --set: {}", source)
+            }
+            Error::SerializeConfig(source) => {
+                write!(f, "failed to serialize the configuration: {}", source)
+            }
+            Error::WriteConfig { path, source } => {
+                write!(f, "failed to write {}: {}", path.display(), source)
+            }
+            Error::ReadRdp { path, source } => {
+                write!(
+                    f,
+                    "failed to read the RDP file {}: {}",
+                    path.display(),
+                    source
+                )
+            }
+            Error::WriteRdp { path, source } => {
+                write!(
+                    f,
+                    "failed to write the RDP file {}: {}",
+                    path.display(),
+                    source
+                )
+            }
+            Error::WriteProfile { path, source } => {
+                write!(
+                    f,
+                    "failed to write the connection profile {}: {}",
+                    path.display(),
+                    source
+                )
+            }
+            Error::WriteOutput(source) => {
+                write!(f, "failed to write to the standard output: {}", source)
+            }
+            Error::LaunchClient { program, source } => {
+                write!(f, "failed to start {}: {}", program, source)
+            }
+            Error::EnumMonitors(source) => write!(f, "failed to list the monitors: {}", source),
+            Error::NoMonitors => write!(f, "no active monitor was found"),
+            Error::ReadFixture { path, source } => {
+                write!(f, "failed to read {}: {}", path.display(), source)
+            }
+            Error::ParseFixture { path, source } => {
+                write!(
+                    f,
+                    "invalid monitors fixture in {}: {}",
+                    path.display(),
+                    source
+                )
+            }
+            Error::UnknownMonitor(id) => write!(f, "no active monitor has the ID {}", id),
+            Error::UnknownPreset(name) => write!(f, "no preset is named \"{}\"", name),
+            Error::UnknownHost(name) => write!(f, "no host is named \"{}\"", name),
+            Error::SelectionWarnings(count) => {
+                write!(f, "the selection has {} warning(s)", count)
+            }
+            Error::Launch(source) => write!(f, "failed to open the app: {}", source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::CreateDirectory { source, .. }
+            | Error::ReadConfig { source, .. }
+            | Error::BackupConfig { source, .. }
+            | Error::WriteConfig { source, .. }
+            | Error::ReadRdp { source, .. }
+            | Error::WriteRdp { source, .. }
+            | Error::WriteProfile { source, .. }
+            | Error::WriteOutput(source)
+            | Error::LaunchClient { source, .. }
+            | Error::ReadFixture { source, .. } => Some(source),
+            Error::ParseConfig { source, .. }
+            | Error::InvalidOverride(source)
+            | Error::SerializeConfig(source)
+            | Error::ParseFixture { source, .. } => Some(source),
+            Error::EnumMonitors(source) => Some(source.as_ref()),
+            Error::Launch(source) => Some(source),
+            Error::Directories
+            | Error::UnsupportedConfigVersion { .. }
+            | Error::InvalidConfig(_)
+            | Error::NoMonitors
+            | Error::UnknownMonitor(_)
+            | Error::UnknownPreset(_)
+            | Error::UnknownHost(_)
+            | Error::SelectionWarnings(_) => None,
+        }
+    }
+}
@@ -0,0 +1,682 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::BTreeMap, str::FromStr};
+
+use druid::{KbKey, Modifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::{app::Direction, config::Config};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Connect,
+    Quit,
+    ToggleMonitor(u32),
+    ToggleHovered,
+    SelectAll,
+    SelectNone,
+    InvertSelection,
+    MoveHover(Direction),
+    ApplyPreset(usize),
+    OpenSettings,
+    ToggleHelp,
+    ToggleDetails,
+    DismissHint,
+}
+
+impl Action {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let action = match name {
+            "connect" => Action::Connect,
+            "cancel" => Action::Quit,
+            "toggle" => Action::ToggleHovered,
+            "select-all" => Action::SelectAll,
+            "select-none" => Action::SelectNone,
+            "invert-selection" => Action::InvertSelection,
+            "move-left" => Action::MoveHover(Direction::Left),
+            "move-right" => Action::MoveHover(Direction::Right),
+            "move-up" => Action::MoveHover(Direction::Up),
+            "move-down" => Action::MoveHover(Direction::Down),
+            "settings" => Action::OpenSettings,
+            "help" => Action::ToggleHelp,
+            "details" => Action::ToggleDetails,
+            "hide-hint" => Action::DismissHint,
+            name => {
+                if let Some(id) = name.strip_prefix("monitor-") {
+                    Action::ToggleMonitor(id.parse().ok()?)
+                } else {
+                    // presets are numbered from 1 like their F keys
+                    let number: usize = name.strip_prefix("preset-")?.parse().ok()?;
+                    Action::ApplyPreset(number.checked_sub(1)?)
+                }
+            }
+        };
+
+        Some(action)
+    }
+
+    /// The keys of the presets that don't exist are left to the other actions.
+    pub fn is_available(self, config: &Config) -> bool {
+        match self {
+            Action::ApplyPreset(index) => index < config.presets.len(),
+            Action::DismissHint => !config.hint_dismissed,
+            _ => true,
+        }
+    }
+
+    pub fn description(self, config: &Config) -> String {
+        match self {
+            Action::Connect => String::from("Starts the remote session on the selected monitors"),
+            Action::Quit => String::from("Closes the app without connecting"),
+            Action::ToggleMonitor(_) => {
+                String::from("Selects or unselects the monitor with that ID")
+            }
+            Action::ToggleHovered => String::from("Selects or unselects the highlighted monitor"),
+            Action::SelectAll => String::from("Selects all the monitors"),
+            Action::SelectNone => String::from("Unselects all the monitors"),
+            Action::InvertSelection => String::from("Inverts the selection"),
+            Action::MoveHover(_) => {
+                String::from("Moves the highlight to the next monitor in that direction")
+            }
+            Action::ApplyPreset(index) => match config.presets.get(index) {
+                Some(preset) => format!("Applies the preset \"{}\"", preset.name),
+                None => String::from("Applies a preset"),
+            },
+            Action::OpenSettings => String::from("Opens the settings panel"),
+            Action::ToggleHelp => String::from("Shows or hides this help"),
+            Action::ToggleDetails => String::from("Shows or hides the details of the monitors"),
+            Action::DismissHint => String::from("Hides the hint for good"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum KeyChords {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyChords {
+    fn chords(&self) -> Vec<(Option<usize>, &str)> {
+        match self {
+            KeyChords::One(chord) => vec![(None, chord.as_str())],
+            KeyChords::Many(chords) => chords
+                .iter()
+                .enumerate()
+                .map(|(index, chord)| (Some(index), chord.as_str()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyChord {
+    pub key: KbKey,
+    pub mods: Modifiers,
+}
+
+impl KeyChord {
+    pub fn new(key: KbKey) -> Self {
+        Self {
+            key,
+            mods: Modifiers::empty(),
+        }
+    }
+
+    /// Letters match in both cases.
+    pub fn matches(&self, key: &KbKey, mods: Modifiers) -> bool {
+        // Shift is only checked when the chord asks for it since it's needed to type some
+        // characters like `?`
+        if self.mods.ctrl() != mods.ctrl()
+            || self.mods.alt() != mods.alt()
+            || self.mods.meta() != mods.meta()
+            || (self.mods.shift() && !mods.shift())
+        {
+            return false;
+        }
+
+        match (&self.key, key) {
+            (KbKey::Character(bound), KbKey::Character(pressed)) => {
+                bound.to_lowercase() == pressed.to_lowercase()
+            }
+            (bound, pressed) => bound == pressed,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        let mut name = String::new();
+
+        for (modifier, modifier_name) in [
+            (Modifiers::CONTROL, "Ctrl+"),
+            (Modifiers::ALT, "Alt+"),
+            (Modifiers::SHIFT, "Shift+"),
+            (Modifiers::META, "Meta+"),
+        ] {
+            if self.mods.contains(modifier) {
+                name.push_str(modifier_name);
+            }
+        }
+
+        name.push_str(&key_name(&self.key));
+        name
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        // the key itself can be a plus sign
+        let (modifiers, key) = match text.strip_suffix("++") {
+            Some(modifiers) => (Some(modifiers), "+"),
+            None => match text.rsplit_once('+') {
+                Some((modifiers, key)) if !key.is_empty() => (Some(modifiers), key),
+                _ => (None, text),
+            },
+        };
+
+        let mut mods = Modifiers::empty();
+
+        // `++` is the plus key without modifiers
+        for modifier in modifiers
+            .filter(|modifiers| !modifiers.trim().is_empty())
+            .into_iter()
+            .flat_map(|modifiers| modifiers.split('+'))
+        {
+            mods |= match modifier.trim().to_lowercase().as_str() {
+                "ctrl" | "control" => Modifiers::CONTROL,
+                "alt" => Modifiers::ALT,
+                "shift" => Modifiers::SHIFT,
+                "meta" | "win" | "super" | "cmd" => Modifiers::META,
+                _ => return Err(format!("\"{}\" isn't a modifier in \"{}\"", modifier, text)),
+            };
+        }
+
+        let key = key.trim();
+
+        Ok(Self {
+            key: parse_key(key).ok_or_else(|| format!("\"{}\" isn't a known key", key))?,
+            mods,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub chord: KeyChord,
+    pub action: Action,
+}
+
+impl Binding {
+    pub fn new(key: KbKey, action: Action) -> Self {
+        Self {
+            chord: KeyChord::new(key),
+            action,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BindingError {
+    pub action: String,
+    pub index: Option<usize>,
+    pub message: String,
+}
+
+pub fn custom_bindings(
+    keybindings: &BTreeMap<String, KeyChords>,
+) -> (Vec<(Action, Vec<Binding>)>, Vec<BindingError>) {
+    let mut bindings = Vec::new();
+    let mut errors = Vec::new();
+
+    for (name, chords) in keybindings {
+        let Some(action) = Action::from_name(name) else {
+            errors.push(BindingError {
+                action: name.clone(),
+                index: None,
+                message: format!("\"{}\" isn't an action", name),
+            });
+
+            continue;
+        };
+
+        let mut action_bindings = Vec::new();
+
+        for (index, chord) in chords.chords() {
+            match chord.parse() {
+                Ok(chord) => action_bindings.push(Binding { chord, action }),
+                Err(message) => errors.push(BindingError {
+                    action: name.clone(),
+                    index,
+                    message,
+                }),
+            }
+        }
+
+        bindings.push((action, action_bindings));
+    }
+
+    (bindings, errors)
+}
+
+/// Returns the default bindings with the ones of the configuration, which replace the default
+/// keys of their actions and take precedence over the other actions.
+pub fn bindings(config: &Config) -> Vec<Binding> {
+    let (custom, _) = custom_bindings(&config.keybindings);
+
+    let mut bindings = custom
+        .iter()
+        .flat_map(|(_, bindings)| bindings.iter().cloned())
+        .collect::<Vec<Binding>>();
+
+    bindings.extend(
+        default_bindings()
+            .into_iter()
+            .filter(|binding| !custom.iter().any(|(action, _)| *action == binding.action)),
+    );
+
+    bindings
+}
+
+pub fn default_bindings() -> Vec<Binding> {
+    let character = |character: &str| KbKey::Character(character.to_string());
+
+    let mut bindings = vec![
+        Binding::new(KbKey::Enter, Action::Connect),
+        Binding::new(KbKey::Escape, Action::Quit),
+        Binding::new(KbKey::Backspace, Action::Quit),
+        Binding::new(KbKey::Delete, Action::Quit),
+    ];
+
+    bindings.extend(
+        (0..10).map(|id| Binding::new(character(&id.to_string()), Action::ToggleMonitor(id))),
+    );
+
+    bindings.extend([
+        Binding::new(character(" "), Action::ToggleHovered),
+        Binding::new(character("a"), Action::SelectAll),
+        Binding::new(character("n"), Action::SelectNone),
+        Binding::new(character("i"), Action::InvertSelection),
+        Binding::new(KbKey::ArrowLeft, Action::MoveHover(Direction::Left)),
+        Binding::new(KbKey::ArrowRight, Action::MoveHover(Direction::Right)),
+        Binding::new(KbKey::ArrowUp, Action::MoveHover(Direction::Up)),
+        Binding::new(KbKey::ArrowDown, Action::MoveHover(Direction::Down)),
+    ]);
+
+    let preset_keys = [
+        KbKey::F1,
+        KbKey::F2,
+        KbKey::F3,
+        KbKey::F4,
+        KbKey::F5,
+        KbKey::F6,
+        KbKey::F7,
+        KbKey::F8,
+        KbKey::F9,
+    ];
+
+    bindings.extend(
+        preset_keys
+            .into_iter()
+            .enumerate()
+            .map(|(index, key)| Binding::new(key, Action::ApplyPreset(index))),
+    );
+
+    // F1 only shows the help when there is no first preset
+    bindings.extend([
+        Binding::new(character("s"), Action::OpenSettings),
+        Binding::new(character("?"), Action::ToggleHelp),
+        Binding::new(KbKey::F1, Action::ToggleHelp),
+        Binding::new(character("d"), Action::ToggleDetails),
+        Binding::new(character("h"), Action::DismissHint),
+    ]);
+
+    bindings
+}
+
+pub fn find_action(
+    bindings: &[Binding],
+    key: &KbKey,
+    mods: Modifiers,
+    config: &Config,
+) -> Option<Action> {
+    bindings
+        .iter()
+        .filter(|binding| binding.chord.matches(key, mods))
+        .map(|binding| binding.action)
+        .find(|action| action.is_available(config))
+}
+
+/// Whether the binding is the one its chord triggers, no earlier available action taking it.
+fn is_active(bindings: &[Binding], binding: &Binding, config: &Config) -> bool {
+    find_action(bindings, &binding.chord.key, binding.chord.mods, config) == Some(binding.action)
+}
+
+pub fn key_names(bindings: &[Binding], action: Action, config: &Config) -> String {
+    bindings
+        .iter()
+        .filter(|binding| binding.action == action && is_active(bindings, binding, config))
+        .map(|binding| binding.chord.name())
+        .collect::<Vec<String>>()
+        .join(" or ")
+}
+
+pub fn help_entries(bindings: &[Binding], config: &Config) -> Vec<(String, String)> {
+    let mut entries: Vec<(Vec<String>, String)> = Vec::new();
+
+    for binding in bindings {
+        if !is_active(bindings, binding, config) {
+            continue;
+        }
+
+        let description = binding.action.description(config);
+        let name = binding.chord.name();
+
+        match entries
+            .iter_mut()
+            .find(|(_, other_description)| *other_description == description)
+        {
+            Some((names, _)) => names.push(name),
+            None => entries.push((vec![name], description)),
+        }
+    }
+
+    entries
+        .into_iter()
+        .map(|(names, description)| (names.join(" "), description))
+        .collect()
+}
+
+pub fn key_name(key: &KbKey) -> String {
+    match key {
+        KbKey::Character(character) if character == " " => String::from("Space"),
+        KbKey::Character(character) => character.to_uppercase(),
+        KbKey::ArrowLeft => String::from("←"),
+        KbKey::ArrowRight => String::from("→"),
+        KbKey::ArrowUp => String::from("↑"),
+        KbKey::ArrowDown => String::from("↓"),
+        key => key.to_string(),
+    }
+}
+
+fn parse_key(name: &str) -> Option<KbKey> {
+    let key = match name.to_lowercase().as_str() {
+        "space" => KbKey::Character(String::from(" ")),
+        "enter" | "return" => KbKey::Enter,
+        "esc" | "escape" => KbKey::Escape,
+        "backspace" => KbKey::Backspace,
+        "del" | "delete" => KbKey::Delete,
+        "tab" => KbKey::Tab,
+        "left" | "arrowleft" => KbKey::ArrowLeft,
+        "right" | "arrowright" => KbKey::ArrowRight,
+        "up" | "arrowup" => KbKey::ArrowUp,
+        "down" | "arrowdown" => KbKey::ArrowDown,
+        "home" => KbKey::Home,
+        "end" => KbKey::End,
+        "pageup" => KbKey::PageUp,
+        "pagedown" => KbKey::PageDown,
+        lowercase if lowercase.chars().count() == 1 => KbKey::Character(lowercase.to_string()),
+        // function keys in any case, like f2
+        lowercase if lowercase.starts_with('f') && lowercase[1..].parse::<u8>().is_ok() => {
+            KbKey::from_str(&lowercase.to_uppercase()).ok()?
+        }
+        _ => match KbKey::from_str(name) {
+            // other words would be taken as text
+            Ok(KbKey::Character(_)) | Err(_) => return None,
+            Ok(key) => key,
+        },
+    };
+
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Directories, Preset};
+
+    fn chord(text: &str) -> KeyChord {
+        text.parse()
+            .unwrap_or_else(|err| panic!("{}: {}", text, err))
+    }
+
+    fn character(character: &str) -> KbKey {
+        KbKey::Character(character.to_string())
+    }
+
+    fn config_with_presets(count: usize) -> Config {
+        let mut config = Config::new(Directories::default());
+        config.presets = (0..count)
+            .map(|index| Preset {
+                name: format!("preset {}", index + 1),
+                monitors: vec![index as u32],
+                base_config_path: None,
+            })
+            .collect();
+
+        config
+    }
+
+    #[test]
+    fn parses_chords() {
+        assert_eq!(
+            chord("Ctrl+Enter"),
+            KeyChord {
+                key: KbKey::Enter,
+                mods: Modifiers::CONTROL,
+            }
+        );
+        assert_eq!(
+            chord("Shift+F1"),
+            KeyChord {
+                key: KbKey::F1,
+                mods: Modifiers::SHIFT,
+            }
+        );
+        assert_eq!(chord("shift+f1"), chord("Shift+F1"));
+        assert_eq!(
+            chord("ctrl + alt + Q"),
+            KeyChord {
+                key: character("q"),
+                mods: Modifiers::CONTROL | Modifiers::ALT,
+            }
+        );
+        assert_eq!(chord("Esc"), KeyChord::new(KbKey::Escape));
+        assert_eq!(chord("space"), KeyChord::new(character(" ")));
+        assert_eq!(chord("PageDown"), KeyChord::new(KbKey::PageDown));
+    }
+
+    #[test]
+    fn parses_the_plus_key() {
+        assert_eq!(chord("+"), KeyChord::new(character("+")));
+        assert_eq!(chord("++"), KeyChord::new(character("+")));
+        assert_eq!(
+            chord("Ctrl++"),
+            KeyChord {
+                key: character("+"),
+                mods: Modifiers::CONTROL,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_modifiers_and_keys() {
+        assert_eq!(
+            "Hyper+a".parse::<KeyChord>(),
+            Err(String::from("\"Hyper\" isn't a modifier in \"Hyper+a\""))
+        );
+        assert_eq!(
+            "Ctrl+Foo".parse::<KeyChord>(),
+            Err(String::from("\"Foo\" isn't a known key"))
+        );
+        assert!("Ctrl++Enter".parse::<KeyChord>().is_err());
+        assert!("".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn matches_modifiers() {
+        let ctrl_enter = chord("Ctrl+Enter");
+
+        assert!(ctrl_enter.matches(&KbKey::Enter, Modifiers::CONTROL));
+        assert!(!ctrl_enter.matches(&KbKey::Enter, Modifiers::empty()));
+        assert!(!ctrl_enter.matches(&KbKey::Enter, Modifiers::CONTROL | Modifiers::ALT));
+
+        // Shift is needed to type some characters, so it only matters when asked for
+        assert!(chord("?").matches(&character("?"), Modifiers::SHIFT));
+        assert!(chord("a").matches(&character("A"), Modifiers::SHIFT));
+        assert!(!chord("Shift+F1").matches(&KbKey::F1, Modifiers::empty()));
+    }
+
+    #[test]
+    fn f1_applies_the_first_preset_when_there_is_one() {
+        let bindings = default_bindings();
+
+        assert_eq!(
+            find_action(
+                &bindings,
+                &KbKey::F1,
+                Modifiers::empty(),
+                &config_with_presets(0)
+            ),
+            Some(Action::ToggleHelp)
+        );
+        assert_eq!(
+            find_action(
+                &bindings,
+                &KbKey::F1,
+                Modifiers::empty(),
+                &config_with_presets(1)
+            ),
+            Some(Action::ApplyPreset(0))
+        );
+        assert_eq!(
+            find_action(
+                &bindings,
+                &KbKey::F2,
+                Modifiers::empty(),
+                &config_with_presets(1)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn only_shows_the_active_keys() {
+        let bindings = default_bindings();
+
+        assert_eq!(
+            key_names(&bindings, Action::ToggleHelp, &config_with_presets(0)),
+            "? or F1"
+        );
+        assert_eq!(
+            key_names(&bindings, Action::ToggleHelp, &config_with_presets(1)),
+            "?"
+        );
+    }
+
+    #[test]
+    fn configured_keys_replace_the_defaults() {
+        let mut config = config_with_presets(1);
+        config
+            .keybindings
+            .insert(String::from("cancel"), KeyChords::One(String::from("Esc")));
+        config.keybindings.insert(
+            String::from("connect"),
+            KeyChords::Many(vec![String::from("Enter"), String::from("Ctrl+Enter")]),
+        );
+        // taken from the monitor with that ID
+        config
+            .keybindings
+            .insert(String::from("help"), KeyChords::One(String::from("1")));
+        config
+            .keybindings
+            .insert(String::from("hide-hint"), KeyChords::Many(Vec::new()));
+
+        let bindings = bindings(&config);
+        let find = |key: KbKey, mods: Modifiers| find_action(&bindings, &key, mods, &config);
+
+        assert_eq!(find(KbKey::Escape, Modifiers::empty()), Some(Action::Quit));
+        assert_eq!(find(KbKey::Backspace, Modifiers::empty()), None);
+        assert_eq!(
+            find(KbKey::Enter, Modifiers::CONTROL),
+            Some(Action::Connect)
+        );
+        assert_eq!(
+            find(character("1"), Modifiers::empty()),
+            Some(Action::ToggleHelp)
+        );
+        assert_eq!(find(character("h"), Modifiers::empty()), None);
+        // the other defaults are kept
+        assert_eq!(
+            find(KbKey::F1, Modifiers::empty()),
+            Some(Action::ApplyPreset(0))
+        );
+        assert_eq!(
+            find(character("2"), Modifiers::empty()),
+            Some(Action::ToggleMonitor(2))
+        );
+        assert_eq!(find(character("?"), Modifiers::empty()), None);
+    }
+
+    #[test]
+    fn reports_invalid_bindings() {
+        let mut keybindings = BTreeMap::new();
+        keybindings.insert(String::from("fly"), KeyChords::One(String::from("f")));
+        keybindings.insert(
+            String::from("connect"),
+            KeyChords::Many(vec![String::from("Enter"), String::from("Hyper+Enter")]),
+        );
+        keybindings.insert(String::from("preset-0"), KeyChords::One(String::from("F1")));
+
+        let (bindings, errors) = custom_bindings(&keybindings);
+
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].0, Action::Connect);
+        assert_eq!(bindings[0].1.len(), 1);
+
+        let errors = errors
+            .into_iter()
+            .map(|error| (error.action, error.index))
+            .collect::<Vec<(String, Option<usize>)>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                (String::from("connect"), Some(1)),
+                (String::from("fly"), None),
+                (String::from("preset-0"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_action_names() {
+        assert_eq!(Action::from_name("cancel"), Some(Action::Quit));
+        assert_eq!(
+            Action::from_name("monitor-3"),
+            Some(Action::ToggleMonitor(3))
+        );
+        assert_eq!(Action::from_name("preset-1"), Some(Action::ApplyPreset(0)));
+        assert_eq!(
+            Action::from_name("move-left"),
+            Some(Action::MoveHover(Direction::Left))
+        );
+        assert_eq!(Action::from_name("preset-0"), None);
+        assert_eq!(Action::from_name("monitor-x"), None);
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::BTreeMap, fmt, path::PathBuf, str::FromStr};
+
+use serde_yaml::{Mapping, Value};
+
+pub const ENV_PREFIX: &str = "INSTANT_DESKTOP_";
+
+/// From the lowest to the highest priority.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    System(PathBuf),
+    User(PathBuf),
+    Environment(String),
+    CommandLine,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::System(path) => write!(f, "system file {}", path.display()),
+            Source::User(path) => write!(f, "user file {}", path.display()),
+            Source::Environment(name) => write!(f, "environment variable {}", name),
+            Source::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+pub type Sources = BTreeMap<String, Source>;
+
+/// A `key=value` override from the command line, where the key can name a nested setting with
+/// dots and the value is parsed as YAML.
+#[derive(Clone, Debug)]
+pub struct Override {
+    pub key: String,
+    pub value: Value,
+}
+
+impl FromStr for Override {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (key, value) = text
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, got \"{}\"", text))?;
+
+        if key.trim().is_empty() {
+            return Err(format!("missing the key in \"{}\"", text));
+        }
+
+        Ok(Self {
+            key: key.trim().to_string(),
+            value: parse_value(value),
+        })
+    }
+}
+
+pub fn env_overrides(vars: impl Iterator<Item = (String, String)>) -> Vec<(String, Override)> {
+    vars.filter_map(|(name, value)| {
+        let key = name.strip_prefix(ENV_PREFIX)?.to_lowercase();
+
+        (!key.is_empty()).then(|| {
+            (
+                name.clone(),
+                Override {
+                    key,
+                    value: parse_value(&value),
+                },
+            )
+        })
+    })
+    .collect()
+}
+
+pub fn merge(config: &mut Mapping, layer: Mapping, source: &Source, sources: &mut Sources) {
+    for (key, value) in layer {
+        if let Some(name) = key.as_str() {
+            sources.insert(name.to_string(), source.clone());
+        }
+
+        merge_value(config, key, value);
+    }
+}
+
+pub fn apply(config: &mut Mapping, setting: &Override, source: &Source, sources: &mut Sources) {
+    let mut keys = setting.key.split('.').rev();
+    let mut value = setting.value.clone();
+
+    // build the nested mappings from the innermost key
+    let top_key = keys.next_back().unwrap_or_default();
+
+    for key in keys {
+        let mut mapping = Mapping::new();
+        mapping.insert(Value::from(key), value);
+        value = Value::Mapping(mapping);
+    }
+
+    let mut layer = Mapping::new();
+    layer.insert(Value::from(top_key), value);
+
+    merge(config, layer, source, sources);
+}
+
+fn merge_value(config: &mut Mapping, key: Value, value: Value) {
+    match (config.get_mut(&key), value) {
+        (Some(Value::Mapping(existing)), Value::Mapping(layer)) => {
+            for (key, value) in layer {
+                merge_value(existing, key, value);
+            }
+        }
+        (_, value) => {
+            config.insert(key, value);
+        }
+    }
+}
+
+/// Parses an override as YAML, so that `false` or `[1, 2]` keep their type, and falls back to
+/// the raw text.
+fn parse_value(text: &str) -> Value {
+    serde_yaml::from_str(text).unwrap_or_else(|_| Value::from(text))
+}
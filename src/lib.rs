@@ -13,7 +13,30 @@
 // limitations under the License.
 
 pub mod app;
+pub mod cli;
+pub mod client;
 pub mod config;
 pub mod connection;
+pub mod diagnostic;
+pub mod diff;
+pub mod error;
+pub mod keys;
+pub mod layer;
+pub mod linux;
+pub mod migration;
+pub mod minimap;
+pub mod monitor;
 pub mod palette;
+pub mod rdp;
+pub mod reload;
+pub mod selection;
+pub mod settings;
+pub mod theme;
+pub mod validation;
+#[cfg(windows)]
 pub mod windows;
+
+#[cfg(not(windows))]
+pub use crate::linux as platform;
+#[cfg(windows)]
+pub use crate::windows as platform;
@@ -0,0 +1,320 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fs, path::Path, process::Command};
+
+use crate::{
+    error::{Error, Result},
+    monitor::{Monitor, MonitorProvider, MonitorRect, Monitors},
+};
+
+const DRM_PATH: &str = "/sys/class/drm";
+
+/// The IDs follow the order of `xrandr --listmonitors`, the primary monitor first, which is also
+/// the order of FreeRDP's `/monitor-list`.
+pub struct LinuxMonitorProvider;
+
+impl MonitorProvider for LinuxMonitorProvider {
+    fn monitors(&self) -> Result<Monitors> {
+        let xrandr_monitors = Command::new("xrandr")
+            .arg("--verbose")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| parse_xrandr(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default();
+
+        let list = if xrandr_monitors.is_empty() {
+            parse_drm(&read_drm_connectors(Path::new(DRM_PATH))?)
+        } else {
+            xrandr_monitors
+        };
+
+        let mut monitors = Monitors::new();
+
+        for monitor in list {
+            monitors.add(monitor);
+        }
+
+        Ok(monitors)
+    }
+}
+
+pub struct DrmConnector {
+    pub name: String,
+    pub status: String,
+    pub modes: String,
+}
+
+pub fn parse_xrandr(output: &str) -> Vec<Monitor> {
+    let mut outputs = output
+        .lines()
+        .filter(|line| !line.starts_with(char::is_whitespace) && !line.starts_with("Screen "))
+        .filter_map(|line| {
+            let mut tokens = line.split_whitespace();
+            let name = tokens.next()?;
+
+            if tokens.next()? != "connected" {
+                return None;
+            }
+
+            let mut primary = false;
+
+            for token in tokens {
+                match token {
+                    "primary" => primary = true,
+                    geometry => return parse_geometry(geometry).map(|rect| (name, rect, primary)),
+                }
+            }
+
+            None
+        })
+        .collect::<Vec<(&str, MonitorRect, bool)>>();
+
+    // stable, so the other outputs keep their order
+    outputs.sort_by_key(|(_, _, primary)| !primary);
+
+    outputs
+        .into_iter()
+        .zip(0..)
+        .map(|((name, rect, primary), id)| {
+            Monitor::new(id, rect, rect, primary).with_name(Some(name.to_string()))
+        })
+        .collect()
+}
+
+/// Side by side in connector order, since sysfs doesn't know about their position.
+pub fn parse_drm(connectors: &[DrmConnector]) -> Vec<Monitor> {
+    let mut connectors = connectors
+        .iter()
+        .filter(|connector| connector.status.trim() == "connected")
+        .filter_map(|connector| {
+            let (width, height) = connector.modes.lines().next()?.trim().split_once('x')?;
+            let height = height.trim_end_matches(|c: char| !c.is_ascii_digit());
+
+            Some((
+                connector.name.as_str(),
+                width.parse().ok()?,
+                height.parse().ok()?,
+            ))
+        })
+        .collect::<Vec<(&str, i32, i32)>>();
+
+    connectors.sort_by_key(|(name, _, _)| *name);
+
+    let mut left = 0;
+
+    connectors
+        .into_iter()
+        .zip(0..)
+        .map(|((name, width, height), id)| {
+            let rect = MonitorRect::new(left, 0, left + width, height);
+            left += width;
+
+            // without the card prefix, like xrandr names the outputs
+            let name = name
+                .split_once('-')
+                .map_or(name, |(_, connector)| connector);
+
+            Monitor::new(id, rect, rect, id == 0).with_name(Some(name.to_string()))
+        })
+        .collect()
+}
+
+fn parse_geometry(geometry: &str) -> Option<MonitorRect> {
+    let (width, position) = geometry.split_once('x')?;
+    let position_start = position.find(['+', '-'])?;
+    let (height, position) = position.split_at(position_start);
+
+    let position = position.strip_prefix('+').unwrap_or(position);
+    let sign_index = position.get(1..)?.find(['+', '-'])? + 1;
+    let (left, top) = position.split_at(sign_index);
+
+    let width: i32 = width.parse().ok()?;
+    let height: i32 = height.parse().ok()?;
+    let left: i32 = left.trim_start_matches('+').parse().ok()?;
+    let top: i32 = top.trim_start_matches('+').parse().ok()?;
+
+    Some(MonitorRect::new(left, top, left + width, top + height))
+}
+
+fn read_drm_connectors(drm_path: &Path) -> Result<Vec<DrmConnector>> {
+    let entries = fs::read_dir(drm_path).map_err(|err| Error::EnumMonitors(Box::new(err)))?;
+
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+
+            Some(DrmConnector {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                status: fs::read_to_string(path.join("status")).ok()?,
+                modes: fs::read_to_string(path.join("modes")).ok()?,
+            })
+        })
+        .collect())
+}
+
+pub fn show_message(message: &str) {
+    println!("{}", message);
+}
+
+pub fn show_error(message: &str) {
+    eprintln!("error: {}", message);
+}
+
+pub fn attach_console() {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures/linux")
+            .join(name)
+    }
+
+    fn describe(monitors: &[Monitor]) -> Vec<(u32, String, MonitorRect, bool)> {
+        monitors
+            .iter()
+            .map(|monitor| {
+                (
+                    monitor.id(),
+                    monitor.name().unwrap_or_default().to_string(),
+                    monitor.rect(),
+                    monitor.is_primary(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_xrandr_verbose_output() {
+        let output = fs::read_to_string(fixture("xrandr_verbose.txt")).unwrap();
+
+        // the primary monitor first, the disconnected and disabled outputs left out
+        assert_eq!(
+            describe(&parse_xrandr(&output)),
+            vec![
+                (
+                    0,
+                    String::from("DP-1"),
+                    MonitorRect::new(0, 0, 2560, 1440),
+                    true
+                ),
+                (
+                    1,
+                    String::from("eDP-1"),
+                    MonitorRect::new(2560, 360, 4480, 1440),
+                    false
+                ),
+                (
+                    2,
+                    String::from("DP-2"),
+                    MonitorRect::new(-1920, 0, 0, 1080),
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_xrandr_output() {
+        let output = fs::read_to_string(fixture("xrandr.txt")).unwrap();
+
+        assert_eq!(
+            describe(&parse_xrandr(&output)),
+            vec![
+                (
+                    0,
+                    String::from("DP-1"),
+                    MonitorRect::new(0, 0, 1920, 1080),
+                    true
+                ),
+                (
+                    1,
+                    String::from("HDMI-1"),
+                    MonitorRect::new(1920, 0, 3840, 1080),
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_connected_but_disabled_outputs() {
+        let output = "HDMI-2 connected (normal left inverted right x axis y axis)\n";
+
+        assert!(parse_xrandr(output).is_empty());
+    }
+
+    #[test]
+    fn parses_geometries() {
+        assert_eq!(
+            parse_geometry("1920x1080+1920+0"),
+            Some(MonitorRect::new(1920, 0, 3840, 1080))
+        );
+        assert_eq!(
+            parse_geometry("1920x1080-1920+0"),
+            Some(MonitorRect::new(-1920, 0, 0, 1080))
+        );
+        assert_eq!(
+            parse_geometry("1080x1920+0-840"),
+            Some(MonitorRect::new(0, -840, 1080, 1080))
+        );
+        assert_eq!(parse_geometry("(normal"), None);
+        assert_eq!(parse_geometry("1920x1080"), None);
+    }
+
+    #[test]
+    fn reads_drm_connectors() {
+        let connectors = read_drm_connectors(&fixture("drm")).unwrap();
+
+        // the card itself has no status
+        let mut names = connectors
+            .iter()
+            .map(|connector| connector.name.as_str())
+            .collect::<Vec<&str>>();
+        names.sort();
+
+        assert_eq!(names, vec!["card0-DP-1", "card0-HDMI-A-1", "card0-eDP-1"]);
+    }
+
+    #[test]
+    fn parses_drm_connectors() {
+        let connectors = read_drm_connectors(&fixture("drm")).unwrap();
+
+        // side by side in connector order, the interlaced mode of HDMI-A-1 included
+        assert_eq!(
+            describe(&parse_drm(&connectors)),
+            vec![
+                (
+                    0,
+                    String::from("HDMI-A-1"),
+                    MonitorRect::new(0, 0, 1920, 1080),
+                    true
+                ),
+                (
+                    1,
+                    String::from("eDP-1"),
+                    MonitorRect::new(1920, 0, 4800, 1800),
+                    false
+                ),
+            ]
+        );
+    }
+}
@@ -14,30 +14,134 @@
 
 #![windows_subsystem = "windows"]
 
+use std::{env, process};
+
+use clap::Parser;
 use druid::AppLauncher;
 use instant_desktop::{
     app::{self, Delegate, State},
-    config::Config,
-    palette,
-    windows::Monitors,
+    cli::{self, Cli, Command},
+    config::{Config, Directories},
+    error::{Error, Result},
+    layer::Override,
+    palette, platform, reload,
+    selection::LastSelection,
 };
+use serde_yaml::Value;
 
 fn main() {
-    let mut config = Config::default();
-    config.load();
+    platform::attach_console();
+
+    let cli = Cli::parse();
+    let command = cli.command();
+    let headless = command.is_some();
+
+    if let Err(err) = run(&cli, command) {
+        if headless {
+            eprintln!("error: {}", err);
+        } else {
+            platform::show_error(&err.to_string());
+        }
+
+        process::exit(1);
+    }
+}
+
+fn run(cli: &Cli, command: Option<Command>) -> Result<()> {
+    let directories = Directories::new()?;
+    let config = load_config(directories.clone(), &cli.overrides, cli.dry_run)?;
+
+    let host = match &cli.host {
+        Some(name) => Some(
+            config
+                .host(name)
+                .ok_or_else(|| Error::UnknownHost(name.clone()))?
+                .clone(),
+        ),
+        None => None,
+    };
+
+    if let Some(command) = command {
+        let config = match &host {
+            Some(host) => config.for_host(host),
+            None => config,
+        };
+
+        if let Command::Config { command } = &command {
+            return command.run(&config);
+        }
+
+        let monitors = cli.monitors()?;
+
+        if command.checks_config() {
+            cli::check_config(&config, Some(&monitors))?;
+        }
+
+        return command.run(&config, &monitors);
+    }
 
-    let active_monitors = Monitors::enum_active().list();
+    // the configuration errors are shown on the overlays, where the settings panel can fix them
+    let monitors = cli.monitors()?;
+
+    let mut active_monitors = monitors.list();
+
+    if let Some(last_selection) = LastSelection::load(&config.directories) {
+        last_selection.apply(&mut active_monitors);
+    }
 
     let mut windows: Vec<u32> = active_monitors.iter().map(|mon| mon.id).collect();
-    let window = app::window_builder(&config, windows.pop().unwrap(), &active_monitors).unwrap();
-    let main_window = window.id;
 
-    AppLauncher::with_window(window)
+    // pick the host first when there are several to choose from
+    let (window, delegate) = if host.is_none() && !config.hosts.is_empty() {
+        let window = app::host_picker_builder(&config, &active_monitors);
+        let picker_window = window.id;
+
+        (window, Delegate::with_host_picker(picker_window, windows))
+    } else {
+        let main_id = windows.pop().ok_or(Error::NoMonitors)?;
+        let window =
+            app::window_builder(&config, main_id, &active_monitors).ok_or(Error::NoMonitors)?;
+        let main_window = window.id;
+
+        (window, Delegate::new(main_window, windows))
+    };
+
+    let launcher = AppLauncher::with_window(window);
+
+    let overrides = cli.overrides.clone();
+    let dry_run = cli.dry_run;
+    reload::watch_config(
+        launcher.get_external_handle(),
+        &config.directories,
+        move || load_config(directories.clone(), &overrides, dry_run),
+    );
+
+    launcher
         .log_to_console()
-        .delegate(Delegate::new(main_window, windows))
-        .configure_env(|env, _| {
-            palette::add_to_env(env);
+        .delegate(delegate)
+        .configure_env(|env, state: &State| {
+            palette::add_to_env(env, state.theme());
         })
-        .launch(State::new(config, active_monitors, u32::default()))
-        .expect("launch failed");
+        .launch(
+            State::new(config, active_monitors, u32::default())
+                .with_host(host.map(|host| host.name)),
+        )
+        .map_err(Error::Launch)
+}
+
+fn load_config(directories: Directories, overrides: &[Override], dry_run: bool) -> Result<Config> {
+    let mut overrides = overrides.to_vec();
+
+    // like --set, so that it's reported as such and never saved
+    if dry_run {
+        overrides.push(Override {
+            key: String::from("dry_run"),
+            value: Value::from(true),
+        });
+    }
+
+    let mut config = Config::new(directories);
+    config.load(env::vars(), &overrides)?;
+
+    Ok(config)
 }
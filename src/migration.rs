@@ -0,0 +1,101 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde_yaml::{Mapping, Value};
+
+pub const CONFIG_VERSION: u32 = 1;
+
+const VERSION_KEY: &str = "version";
+
+/// Indexed by the version they migrate from.
+const MIGRATIONS: [fn(&mut Mapping); CONFIG_VERSION as usize] = [from_v0];
+
+/// Files written before versioning are version 0.
+pub fn config_version(config: &Value) -> u32 {
+    config
+        .get(VERSION_KEY)
+        .and_then(Value::as_u64)
+        .and_then(|version| u32::try_from(version).ok())
+        .unwrap_or(0)
+}
+
+/// Returns the original version. Configurations from a newer version are left untouched.
+pub fn migrate(config: &mut Value) -> u32 {
+    let version = config_version(config);
+
+    if version >= CONFIG_VERSION {
+        return version;
+    }
+
+    // an empty file parses as null
+    if !config.is_mapping() {
+        *config = Value::Mapping(Mapping::new());
+    }
+
+    if let Value::Mapping(mapping) = config {
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(mapping);
+        }
+
+        mapping.insert(Value::from(VERSION_KEY), Value::from(CONFIG_VERSION));
+    }
+
+    version
+}
+
+/// Files from before versioning only lack the version, the settings added since then all have
+/// defaults.
+fn from_v0(_config: &mut Mapping) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> Value {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn files_without_version_are_version_0() {
+        assert_eq!(config_version(&parse("fullscreen: true")), 0);
+        assert_eq!(config_version(&parse("version: 1")), 1);
+        assert_eq!(config_version(&parse("version: nope")), 0);
+    }
+
+    #[test]
+    fn migrates_to_the_current_version() {
+        let mut config = parse("fullscreen: false");
+
+        assert_eq!(migrate(&mut config), 0);
+        assert_eq!(config_version(&config), CONFIG_VERSION);
+        assert_eq!(config.get("fullscreen"), Some(&Value::from(false)));
+    }
+
+    #[test]
+    fn migrates_an_empty_file() {
+        let mut config = parse("");
+
+        assert_eq!(migrate(&mut config), 0);
+        assert_eq!(config, parse(&format!("version: {}", CONFIG_VERSION)));
+    }
+
+    #[test]
+    fn leaves_newer_versions_untouched() {
+        let mut config = parse("version: 99\nnew_setting: true");
+        let original = config.clone();
+
+        assert_eq!(migrate(&mut config), 99);
+        assert_eq!(config, original);
+    }
+}
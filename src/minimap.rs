@@ -0,0 +1,185 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use druid::{
+    im::Vector,
+    kurbo::RoundedRect,
+    piet::{FontFamily, Text, TextLayout, TextLayoutBuilder},
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, MouseButton,
+    PaintCtx, Point, Rect, RenderContext, Selector, Size, UpdateCtx, Widget,
+};
+
+use crate::{monitor::Monitor, palette};
+
+pub const TOGGLE_MONITOR: Selector<u32> = Selector::new("instant-desktop.toggle-monitor");
+
+/// The largest size of the monitors in the map, which keeps the proportions of the desktop.
+const MAP_SIZE: Size = Size::new(240.0, 160.0);
+const PADDING: f64 = 8.0;
+const GAP: f64 = 1.0;
+
+pub struct MiniMap {
+    id: u32,
+    pressed: Option<u32>,
+}
+
+impl MiniMap {
+    pub fn new(id: u32) -> Self {
+        Self { id, pressed: None }
+    }
+}
+
+impl Widget<Vector<Monitor>> for MiniMap {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut Vector<Monitor>, _env: &Env) {
+        match event {
+            Event::MouseDown(mouse) if mouse.button == MouseButton::Left => {
+                self.pressed = monitor_at(data, ctx.size(), mouse.pos);
+                ctx.set_active(true);
+                ctx.set_handled();
+            }
+            Event::MouseUp(mouse) if ctx.is_active() && mouse.button == MouseButton::Left => {
+                ctx.set_active(false);
+                ctx.set_handled();
+
+                if let Some(id) = self.pressed.take() {
+                    if monitor_at(data, ctx.size(), mouse.pos) == Some(id) {
+                        ctx.submit_command(TOGGLE_MONITOR.with(id));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        _ctx: &mut LifeCycleCtx,
+        _event: &LifeCycle,
+        _data: &Vector<Monitor>,
+        _env: &Env,
+    ) {
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: &Vector<Monitor>,
+        data: &Vector<Monitor>,
+        _env: &Env,
+    ) {
+        if !old_data.same(data) {
+            ctx.request_layout();
+        } else if ctx.env_changed() {
+            ctx.request_paint();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &Vector<Monitor>,
+        _env: &Env,
+    ) -> Size {
+        let bounds = desktop_bounds(data);
+        let scale = (MAP_SIZE.width / bounds.width()).min(MAP_SIZE.height / bounds.height());
+
+        bc.constrain(Size::new(
+            bounds.width() * scale + PADDING * 2.0,
+            bounds.height() * scale + PADDING * 2.0,
+        ))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &Vector<Monitor>, env: &Env) {
+        let background = RoundedRect::from_rect(ctx.size().to_rect(), 4.0);
+        ctx.fill(background, &env.get(palette::PANEL_BACKGROUND_COLOR));
+
+        for (monitor, rect) in monitor_rects(data, ctx.size()) {
+            let rect = rect.inset(-GAP);
+
+            let (fill, text) = if monitor.selected {
+                (env.get(palette::PRIMARY), env.get(palette::DARK))
+            } else {
+                (env.get(palette::LIGHT), env.get(palette::TEXT))
+            };
+
+            ctx.fill(rect, &fill);
+
+            if monitor.id == self.id {
+                ctx.stroke(rect.inset(-1.0), &env.get(palette::TEXT), 2.0);
+            }
+
+            let font_size = (rect.height() * 0.6).min(16.0);
+
+            if font_size < 6.0 {
+                continue;
+            }
+
+            let layout = ctx
+                .text()
+                .new_text_layout(monitor.id.to_string())
+                .font(FontFamily::SYSTEM_UI, font_size)
+                .text_color(text)
+                .build();
+
+            if let Ok(layout) = layout {
+                let size = layout.size();
+                let origin = rect.center() - (size.to_vec2() / 2.0);
+
+                ctx.draw_text(&layout, origin);
+            }
+        }
+    }
+}
+
+fn desktop_bounds(monitors: &Vector<Monitor>) -> Rect {
+    monitors
+        .iter()
+        .map(monitor_rect)
+        .reduce(|bounds, rect| bounds.union(rect))
+        .filter(|bounds| bounds.area() > 0.0)
+        .unwrap_or_else(|| MAP_SIZE.to_rect())
+}
+
+fn monitor_rects(monitors: &Vector<Monitor>, size: Size) -> Vec<(&Monitor, Rect)> {
+    let bounds = desktop_bounds(monitors);
+    let scale = ((size.width - PADDING * 2.0) / bounds.width())
+        .min((size.height - PADDING * 2.0) / bounds.height());
+
+    monitors
+        .iter()
+        .map(|monitor| {
+            let rect = monitor_rect(monitor);
+            let origin = Point::new(PADDING, PADDING) + (rect.origin() - bounds.origin()) * scale;
+
+            (monitor, Rect::from_origin_size(origin, rect.size() * scale))
+        })
+        .collect()
+}
+
+fn monitor_at(monitors: &Vector<Monitor>, size: Size, pos: Point) -> Option<u32> {
+    monitor_rects(monitors, size)
+        .into_iter()
+        .find(|(_, rect)| rect.contains(pos))
+        .map(|(monitor, _)| monitor.id)
+}
+
+fn monitor_rect(monitor: &Monitor) -> Rect {
+    Rect::new(
+        monitor.left().into(),
+        monitor.top().into(),
+        monitor.right().into(),
+        monitor.bottom().into(),
+    )
+}
@@ -0,0 +1,534 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fs, path::Path};
+
+use druid::{im::Vector, Data};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+#[cfg(not(windows))]
+use crate::linux::LinuxMonitorProvider;
+#[cfg(windows)]
+use crate::windows::Win32MonitorProvider;
+
+/// The DPI of a monitor at a 100% scale.
+pub const BASE_DPI: u32 = 96;
+
+/// With exclusive right and bottom edges.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Data, Serialize, Deserialize)]
+pub struct MonitorRect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl MonitorRect {
+    pub fn new(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    pub fn center(&self) -> (i32, i32) {
+        (self.left + self.width() / 2, self.top + self.height() / 2)
+    }
+
+    pub fn scaled(&self, scale: f64) -> Self {
+        let scale = |value: i32| (f64::from(value) / scale).round() as i32;
+
+        Self::new(
+            scale(self.left),
+            scale(self.top),
+            scale(self.right),
+            scale(self.bottom),
+        )
+    }
+}
+
+/// A monitor, whose geometry is in physical pixels; the scaled geometry gives the display
+/// points in which its windows are sized and positioned.
+#[derive(Clone, Debug, Data)]
+pub struct Monitor {
+    pub id: u32,
+    pub selected: bool,
+    rect: MonitorRect,
+    work_area: MonitorRect,
+    primary: bool,
+    scale: f64,
+    name: Option<String>,
+}
+
+impl Monitor {
+    pub fn new(id: u32, rect: MonitorRect, work_area: MonitorRect, primary: bool) -> Self {
+        Monitor {
+            id,
+            selected: false,
+            rect,
+            work_area,
+            primary,
+            scale: 1.0,
+            name: None,
+        }
+    }
+
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_dpi(self, dpi: u32) -> Self {
+        self.with_scale(f64::from(dpi) / f64::from(BASE_DPI))
+    }
+
+    pub fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name.filter(|name| !name.trim().is_empty());
+        self
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn rect(&self) -> MonitorRect {
+        self.rect
+    }
+
+    pub fn work_area(&self) -> MonitorRect {
+        self.work_area
+    }
+
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn dpi(&self) -> u32 {
+        (self.scale * f64::from(BASE_DPI)).round() as u32
+    }
+
+    pub fn scaled_rect(&self) -> MonitorRect {
+        self.rect.scaled(self.scale)
+    }
+
+    pub fn scaled_work_area(&self) -> MonitorRect {
+        self.work_area.scaled(self.scale)
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn left(&self) -> i32 {
+        self.rect.left
+    }
+
+    pub fn top(&self) -> i32 {
+        self.rect.top
+    }
+
+    pub fn right(&self) -> i32 {
+        self.rect.right
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.rect.bottom
+    }
+
+    pub fn width(&self) -> i32 {
+        self.rect.width()
+    }
+
+    pub fn height(&self) -> i32 {
+        self.rect.height()
+    }
+
+    pub fn work_left(&self) -> i32 {
+        self.work_area.left
+    }
+
+    pub fn work_top(&self) -> i32 {
+        self.work_area.top
+    }
+
+    pub fn work_right(&self) -> i32 {
+        self.work_area.right
+    }
+
+    pub fn work_bottom(&self) -> i32 {
+        self.work_area.bottom
+    }
+
+    pub fn work_width(&self) -> i32 {
+        self.work_area.width()
+    }
+
+    pub fn work_height(&self) -> i32 {
+        self.work_area.height()
+    }
+
+    pub fn info_str(&self) -> String {
+        format!(
+            "{}: {} x {}; ({}, {}, {}, {})",
+            self.id(),
+            self.width(),
+            self.height(),
+            self.left(),
+            self.top(),
+            self.right() - 1,
+            self.bottom() - 1
+        )
+    }
+
+    pub fn details_str(&self) -> String {
+        let mut lines = Vec::new();
+
+        if let Some(name) = self.name() {
+            lines.push(name.to_string());
+        }
+
+        lines.push(format!(
+            "{} x {} at ({}, {})",
+            self.width(),
+            self.height(),
+            self.left(),
+            self.top()
+        ));
+        lines.push(format!(
+            "Scale {}% ({} DPI)",
+            (self.scale * 100.0).round(),
+            self.dpi()
+        ));
+
+        if self.primary {
+            lines.push(String::from("Primary monitor"));
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[derive(Clone, Default, Data)]
+pub struct Monitors {
+    list: Vector<Monitor>,
+}
+
+impl Monitors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enum_active() -> Result<Self> {
+        #[cfg(windows)]
+        return Win32MonitorProvider.monitors();
+
+        #[cfg(not(windows))]
+        return LinuxMonitorProvider.monitors();
+    }
+
+    pub fn add(&mut self, monitor: Monitor) {
+        self.list.push_back(monitor);
+    }
+
+    pub fn list(&self) -> Vector<Monitor> {
+        self.list.clone()
+    }
+}
+
+pub trait MonitorProvider {
+    fn monitors(&self) -> Result<Monitors>;
+}
+
+#[derive(Clone, Default)]
+pub struct StaticMonitorProvider {
+    monitors: Vec<Monitor>,
+}
+
+impl StaticMonitorProvider {
+    pub fn new(monitors: Vec<Monitor>) -> Self {
+        Self { monitors }
+    }
+
+    /// Numbers the monitors in order starting from 0, the first one being the primary monitor.
+    pub fn from_rects(rects: impl IntoIterator<Item = (MonitorRect, MonitorRect)>) -> Self {
+        let monitors = rects
+            .into_iter()
+            .zip(0..)
+            .map(|((rect, work_area), id)| Monitor::new(id, rect, work_area, id == 0))
+            .collect();
+
+        Self { monitors }
+    }
+
+    pub fn from_yaml(yaml: &str) -> std::result::Result<Self, serde_yaml::Error> {
+        let fixture: MonitorsFixture = serde_yaml::from_str(yaml)?;
+
+        let monitors = fixture
+            .monitors
+            .into_iter()
+            .map(|monitor| {
+                Monitor::new(
+                    monitor.id,
+                    monitor.rect,
+                    monitor.work_area.unwrap_or(monitor.rect),
+                    monitor.primary,
+                )
+                .with_scale(monitor.scale)
+                .with_name(monitor.name)
+            })
+            .collect();
+
+        Ok(Self { monitors })
+    }
+
+    pub fn from_fixture(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        let fixture_content = fs::read_to_string(path).map_err(|source| Error::ReadFixture {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Self::from_yaml(&fixture_content).map_err(|source| Error::ParseFixture {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+impl MonitorProvider for StaticMonitorProvider {
+    fn monitors(&self) -> Result<Monitors> {
+        let mut monitors = Monitors::new();
+
+        for monitor in &self.monitors {
+            monitors.add(monitor.clone());
+        }
+
+        Ok(monitors)
+    }
+}
+
+#[derive(Deserialize)]
+struct MonitorsFixture {
+    monitors: Vec<MonitorFixture>,
+}
+
+/// The work area defaults to the whole monitor.
+#[derive(Deserialize)]
+struct MonitorFixture {
+    id: u32,
+    rect: MonitorRect,
+    work_area: Option<MonitorRect>,
+    #[serde(default)]
+    primary: bool,
+    #[serde(default = "default_scale")]
+    scale: f64,
+    name: Option<String>,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+pub(crate) fn join_ids(ids: &[u32]) -> String {
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    type Expected = (u32, MonitorRect, MonitorRect, bool, f64);
+
+    fn fixtures_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/monitors")
+    }
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> MonitorRect {
+        MonitorRect::new(left, top, right, bottom)
+    }
+
+    fn expected_fixtures() -> Vec<(&'static str, Vec<Expected>)> {
+        let full_hd = rect(0, 0, 1920, 1080);
+        let full_hd_work_area = rect(0, 0, 1920, 1040);
+
+        vec![
+            (
+                "gap.yaml",
+                vec![
+                    (0, full_hd, full_hd_work_area, true, 1.0),
+                    (
+                        1,
+                        rect(2020, 0, 3940, 1080),
+                        rect(2020, 0, 3940, 1080),
+                        false,
+                        1.0,
+                    ),
+                ],
+            ),
+            (
+                "mixed_scale.yaml",
+                vec![
+                    (0, rect(0, 0, 2880, 1800), rect(0, 0, 2880, 1752), true, 1.5),
+                    (
+                        1,
+                        rect(2880, 0, 4800, 1080),
+                        rect(2880, 0, 4800, 1080),
+                        false,
+                        1.0,
+                    ),
+                ],
+            ),
+            (
+                "negative_coordinates.yaml",
+                vec![
+                    (0, rect(0, 0, 2560, 1440), rect(0, 0, 2560, 1400), true, 1.0),
+                    (
+                        1,
+                        rect(-1920, -360, 0, 720),
+                        rect(-1920, -360, 0, 720),
+                        false,
+                        1.0,
+                    ),
+                    (
+                        2,
+                        rect(320, -1080, 2240, 0),
+                        rect(320, -1080, 2240, 0),
+                        false,
+                        1.0,
+                    ),
+                ],
+            ),
+            (
+                "portrait.yaml",
+                vec![
+                    (0, full_hd, full_hd_work_area, true, 1.0),
+                    (
+                        1,
+                        rect(-1080, -420, 0, 1500),
+                        rect(-1080, -420, 0, 1500),
+                        false,
+                        1.0,
+                    ),
+                    (
+                        2,
+                        rect(1920, -420, 3000, 1500),
+                        rect(1920, -420, 3000, 1500),
+                        false,
+                        1.0,
+                    ),
+                ],
+            ),
+            (
+                "side_by_side.yaml",
+                vec![
+                    (0, full_hd, full_hd_work_area, true, 1.0),
+                    (
+                        1,
+                        rect(-1920, 0, 0, 1080),
+                        rect(-1920, 0, 0, 1080),
+                        false,
+                        1.0,
+                    ),
+                    (
+                        2,
+                        rect(1920, 0, 3840, 1080),
+                        rect(1920, 0, 3840, 1080),
+                        false,
+                        1.0,
+                    ),
+                ],
+            ),
+            (
+                "single.yaml",
+                vec![(0, full_hd, full_hd_work_area, true, 1.0)],
+            ),
+        ]
+    }
+
+    #[test]
+    fn every_fixture_is_covered() {
+        let mut files = fs::read_dir(fixtures_dir())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect::<Vec<String>>();
+        files.sort();
+
+        let expected = expected_fixtures()
+            .into_iter()
+            .map(|(file, _)| file.to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(files, expected);
+    }
+
+    #[test]
+    fn loads_the_fixtures() {
+        for (file, expected) in expected_fixtures() {
+            let monitors = StaticMonitorProvider::from_fixture(fixtures_dir().join(file))
+                .and_then(|provider| provider.monitors())
+                .unwrap_or_else(|err| panic!("{}: {}", file, err))
+                .list()
+                .into_iter()
+                .map(|monitor| {
+                    (
+                        monitor.id(),
+                        monitor.rect(),
+                        monitor.work_area(),
+                        monitor.is_primary(),
+                        monitor.scale(),
+                    )
+                })
+                .collect::<Vec<Expected>>();
+
+            assert_eq!(monitors, expected, "{}", file);
+        }
+    }
+
+    #[test]
+    fn scales_the_geometry() {
+        let monitor = Monitor::new(
+            0,
+            rect(2880, -1800, 5760, 0),
+            rect(2880, -1800, 5760, -48),
+            true,
+        )
+        .with_dpi(144);
+
+        assert_eq!(monitor.scale(), 1.5);
+        assert_eq!(monitor.dpi(), 144);
+        assert_eq!(monitor.scaled_rect(), rect(1920, -1200, 3840, 0));
+        assert_eq!(monitor.scaled_work_area(), rect(1920, -1200, 3840, -32));
+    }
+}
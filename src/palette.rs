@@ -14,23 +14,45 @@
 
 use druid::{Color, Env, Key};
 
+use crate::theme::Theme;
+
 pub const PRIMARY: Key<Color> = Key::new("instant_desktop.palette.primary");
 pub const LIGHT: Key<Color> = Key::new("instant_desktop.palette.light");
 pub const LIGHT_HOVERED: Key<Color> = Key::new("instant_desktop.palette.light_hovered");
 pub const DARK: Key<Color> = Key::new("instant_desktop.palette.dark");
+pub const BACKGROUND: Key<Color> = Key::new("instant_desktop.palette.background");
+pub const TEXT: Key<Color> = Key::new("instant_desktop.palette.text");
 
 pub const MONITOR_BACKGROUND_COLOR: Key<Color> =
     Key::new("instant_desktop.palette.monitor_background_color");
 pub const ID_BACKGROUND_COLOR: Key<Color> = Key::new("instant_desktop.palette.id_background_color");
 pub const ID_TEXT_COLOR: Key<Color> = Key::new("instant_desktop.palette.id_text_color");
+pub const WARNING_BACKGROUND_COLOR: Key<Color> =
+    Key::new("instant_desktop.palette.warning_background_color");
+pub const WARNING_TEXT_COLOR: Key<Color> = Key::new("instant_desktop.palette.warning_text_color");
+pub const PANEL_BACKGROUND_COLOR: Key<Color> =
+    Key::new("instant_desktop.palette.panel_background_color");
+
+pub const ID_FONT_SIZE: Key<f64> = Key::new("instant_desktop.palette.id_font_size");
+pub const ID_BOX_SIZE: Key<f64> = Key::new("instant_desktop.palette.id_box_size");
 
-pub fn add_to_env(env: &mut Env) {
-    env.set(PRIMARY, Color::rgb8(0xff, 0xd2, 0x00));
-    env.set(LIGHT, Color::rgb8(0x8a, 0x91, 0x99));
-    env.set(LIGHT_HOVERED, Color::rgb8(0xbf, 0xc4, 0xca));
-    env.set(DARK, Color::rgb8(0x33, 0x31, 0x32));
+pub fn add_to_env(env: &mut Env, theme: &Theme) {
+    let palette = theme.palette();
 
-    env.set(MONITOR_BACKGROUND_COLOR, Color::BLACK);
+    env.set(PRIMARY, palette.primary);
+    env.set(LIGHT, palette.light);
+    env.set(LIGHT_HOVERED, palette.light_hovered);
+    env.set(DARK, palette.dark);
+    env.set(BACKGROUND, palette.background);
+    env.set(TEXT, palette.text);
+
+    env.set(MONITOR_BACKGROUND_COLOR, env.get(BACKGROUND));
     env.set(ID_BACKGROUND_COLOR, env.get(LIGHT));
-    env.set(ID_TEXT_COLOR, Color::WHITE);
+    env.set(ID_TEXT_COLOR, env.get(TEXT));
+    env.set(WARNING_BACKGROUND_COLOR, palette.warning_background);
+    env.set(WARNING_TEXT_COLOR, palette.warning_text);
+    env.set(PANEL_BACKGROUND_COLOR, palette.panel_background);
+
+    env.set(ID_FONT_SIZE, theme.id_font_size());
+    env.set(ID_BOX_SIZE, theme.id_box_size());
 }
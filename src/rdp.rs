@@ -0,0 +1,600 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt, fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+const DEFAULT_LINE_ENDING: &str = "\r\n";
+
+const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+const UTF16_LE_BOM: [u8; 2] = [0xff, 0xfe];
+const UTF16_BE_BOM: [u8; 2] = [0xfe, 0xff];
+
+/// Text encoding of an `.rdp` file on disk, including whether it starts with a BOM so that the
+/// file is written back the way it was read.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16LeBom,
+    Utf16Be,
+    Utf16BeBom,
+}
+
+impl Encoding {
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&UTF8_BOM) {
+            Encoding::Utf8Bom
+        } else if bytes.starts_with(&UTF16_LE_BOM) {
+            Encoding::Utf16LeBom
+        } else if bytes.starts_with(&UTF16_BE_BOM) {
+            Encoding::Utf16BeBom
+        } else {
+            // without a BOM, ASCII text encoded as UTF-16 has a zero in every other byte
+            match bytes {
+                [first, 0, ..] if *first != 0 => Encoding::Utf16Le,
+                [0, second, ..] if *second != 0 => Encoding::Utf16Be,
+                _ => Encoding::Utf8,
+            }
+        }
+    }
+
+    pub fn decode(self, bytes: &[u8]) -> io::Result<String> {
+        match self {
+            Encoding::Utf8 | Encoding::Utf8Bom => {
+                let bytes = bytes.strip_prefix(&UTF8_BOM[..]).unwrap_or(bytes);
+
+                String::from_utf8(bytes.to_vec())
+                    .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+            }
+            Encoding::Utf16Le | Encoding::Utf16LeBom | Encoding::Utf16Be | Encoding::Utf16BeBom => {
+                let little_endian = self.is_little_endian();
+                let bom = if little_endian {
+                    &UTF16_LE_BOM
+                } else {
+                    &UTF16_BE_BOM
+                };
+                let bytes = bytes.strip_prefix(&bom[..]).unwrap_or(bytes);
+
+                if !bytes.len().is_multiple_of(2) {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        "odd number of bytes in UTF-16 text",
+                    ));
+                }
+
+                let units = bytes
+                    .chunks_exact(2)
+                    .map(|pair| {
+                        if little_endian {
+                            u16::from_le_bytes([pair[0], pair[1]])
+                        } else {
+                            u16::from_be_bytes([pair[0], pair[1]])
+                        }
+                    })
+                    .collect::<Vec<u16>>();
+
+                String::from_utf16(&units)
+                    .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+            }
+        }
+    }
+
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        let bytes = match self {
+            Encoding::Utf8 | Encoding::Utf8Bom => text.as_bytes().to_vec(),
+            _ if self.is_little_endian() => {
+                text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+            }
+            _ => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+        };
+
+        [self.bom(), &bytes].concat()
+    }
+
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 | Encoding::Utf16Le | Encoding::Utf16Be => &[],
+            Encoding::Utf8Bom => &UTF8_BOM,
+            Encoding::Utf16LeBom => &UTF16_LE_BOM,
+            Encoding::Utf16BeBom => &UTF16_BE_BOM,
+        }
+    }
+
+    fn is_little_endian(self) -> bool {
+        matches!(self, Encoding::Utf16Le | Encoding::Utf16LeBom)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RdpValue {
+    Integer(i32),
+    String(String),
+    Binary(Vec<u8>),
+}
+
+impl RdpValue {
+    fn parse(kind: &str, raw: &str) -> Option<Self> {
+        match kind {
+            "i" => raw.trim().parse().ok().map(RdpValue::Integer),
+            "s" => Some(RdpValue::String(raw.to_string())),
+            "b" => parse_hex(raw.trim()).map(RdpValue::Binary),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> char {
+        match self {
+            RdpValue::Integer(_) => 'i',
+            RdpValue::String(_) => 's',
+            RdpValue::Binary(_) => 'b',
+        }
+    }
+}
+
+impl fmt::Display for RdpValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RdpValue::Integer(value) => write!(f, "{}", value),
+            RdpValue::String(value) => write!(f, "{}", value),
+            RdpValue::Binary(bytes) => bytes.iter().try_for_each(|byte| write!(f, "{:02X}", byte)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Setting {
+    key: String,
+    value: RdpValue,
+}
+
+impl Setting {
+    fn parse(text: &str) -> Option<Self> {
+        let (key, rest) = text.split_once(':')?;
+        let (kind, raw) = rest.split_once(':')?;
+        let key = key.trim();
+
+        if key.is_empty() {
+            return None;
+        }
+
+        RdpValue::parse(kind, raw).map(|value| Setting {
+            key: key.to_string(),
+            value,
+        })
+    }
+
+    fn matches(&self, key: &str) -> bool {
+        self.key.eq_ignore_ascii_case(key.trim())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Line {
+    text: String,
+    ending: &'static str,
+    setting: Option<Setting>,
+}
+
+/// Keeps every line it doesn't modify exactly as it was read. Keys are matched
+/// case-insensitively, and `set` updates the first of duplicate keys and drops the others.
+#[derive(Clone, Debug, Default)]
+pub struct RdpFile {
+    lines: Vec<Line>,
+    encoding: Encoding,
+}
+
+impl RdpFile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut rest = content;
+
+        while !rest.is_empty() {
+            let (text, ending, next) = match rest.find('\n') {
+                Some(index) if rest[..index].ends_with('\r') => {
+                    (&rest[..index - 1], "\r\n", &rest[index + 1..])
+                }
+                Some(index) => (&rest[..index], "\n", &rest[index + 1..]),
+                None => (rest, "", ""),
+            };
+
+            lines.push(Line {
+                text: text.to_string(),
+                ending,
+                setting: Setting::parse(text),
+            });
+
+            rest = next;
+        }
+
+        Self {
+            lines,
+            encoding: Encoding::default(),
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let encoding = Encoding::detect(bytes);
+        let mut rdp_file = Self::parse(&encoding.decode(bytes)?);
+        rdp_file.encoding = encoding;
+
+        Ok(rdp_file)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encoding.encode(&self.to_string())
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.encoding = encoding;
+    }
+
+    pub fn get(&self, key: &str) -> Option<&RdpValue> {
+        self.settings()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key.trim()))
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_integer(&self, key: &str) -> Option<i32> {
+        match self.get(key) {
+            Some(RdpValue::Integer(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_string(&self, key: &str) -> Option<&str> {
+        match self.get(key) {
+            Some(RdpValue::String(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, key: &str, value: RdpValue) {
+        let text = format!("{}:{}:{}", key.trim(), value.kind(), value);
+        let mut found = false;
+
+        self.lines.retain_mut(|line| match &mut line.setting {
+            Some(setting) if setting.matches(key) => {
+                if found {
+                    return false;
+                }
+
+                found = true;
+
+                if setting.value != value {
+                    setting.value = value.clone();
+                    line.text = format!("{}:{}:{}", setting.key, value.kind(), value);
+                }
+
+                true
+            }
+            _ => true,
+        });
+
+        if !found {
+            let ending = self.line_ending();
+
+            if let Some(last) = self.lines.last_mut() {
+                if last.ending.is_empty() {
+                    last.ending = ending;
+                }
+            }
+
+            self.lines.push(Line {
+                setting: Setting::parse(&text),
+                text,
+                ending,
+            });
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<RdpValue> {
+        let removed = self.get(key).cloned();
+
+        self.lines.retain(|line| {
+            line.setting
+                .as_ref()
+                .is_none_or(|setting| !setting.matches(key))
+        });
+
+        removed
+    }
+
+    pub fn settings(&self) -> impl Iterator<Item = (&str, &RdpValue)> {
+        self.lines
+            .iter()
+            .filter_map(|line| line.setting.as_ref())
+            .map(|setting| (setting.key.as_str(), &setting.value))
+    }
+
+    /// Returns the 1-based number and text of the lines that aren't blank but aren't valid
+    /// settings either.
+    pub fn invalid_lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.lines
+            .iter()
+            .zip(1..)
+            .filter(|(line, _)| line.setting.is_none() && !line.text.trim().is_empty())
+            .map(|(line, number)| (number, line.text.as_str()))
+    }
+
+    fn line_ending(&self) -> &'static str {
+        self.lines
+            .iter()
+            .map(|line| line.ending)
+            .find(|ending| !ending.is_empty())
+            .unwrap_or(DEFAULT_LINE_ENDING)
+    }
+}
+
+impl fmt::Display for RdpFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.lines
+            .iter()
+            .try_for_each(|line| write!(f, "{}{}", line.text, line.ending))
+    }
+}
+
+fn parse_hex(raw: &str) -> Option<Vec<u8>> {
+    if !raw.len().is_multiple_of(2) || !raw.is_ascii() {
+        return None;
+    }
+
+    (0..raw.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&raw[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(content: &str) {
+        assert_eq!(RdpFile::parse(content).to_string(), content);
+    }
+
+    #[test]
+    fn keeps_mixed_line_endings() {
+        round_trip("full address:s:host\r\nscreen mode id:i:2\nuse multimon:i:1\r\n");
+    }
+
+    #[test]
+    fn keeps_missing_trailing_newline() {
+        round_trip("full address:s:host\r\nscreen mode id:i:2");
+    }
+
+    #[test]
+    fn keeps_comments_and_unknown_types() {
+        round_trip(
+            "; a comment\r\n\r\nfull address:s:host\r\nfuture setting:x:42\r\nnot a setting\r\n",
+        );
+    }
+
+    #[test]
+    fn keeps_duplicate_keys_until_set() {
+        let content = "use multimon:i:0\r\nfull address:s:host\r\nUse Multimon:i:1\r\n";
+        round_trip(content);
+
+        let mut rdp_file = RdpFile::parse(content);
+        assert_eq!(rdp_file.get_integer("use multimon"), Some(0));
+
+        rdp_file.set("use multimon", RdpValue::Integer(1));
+        assert_eq!(
+            rdp_file.to_string(),
+            "use multimon:i:1\r\nfull address:s:host\r\n"
+        );
+    }
+
+    #[test]
+    fn set_keeps_unchanged_lines_and_appends_new_keys() {
+        let mut rdp_file = RdpFile::parse("Full Address:s:host\nscreen mode id:i:2");
+
+        rdp_file.set("full address", RdpValue::String(String::from("host")));
+        rdp_file.set("selectedmonitors", RdpValue::String(String::from("0,1")));
+
+        assert_eq!(
+            rdp_file.to_string(),
+            "Full Address:s:host\nscreen mode id:i:2\nselectedmonitors:s:0,1\n"
+        );
+    }
+
+    #[test]
+    fn set_uses_crlf_in_an_empty_file() {
+        let mut rdp_file = RdpFile::new();
+        rdp_file.set("use multimon", RdpValue::Integer(1));
+
+        assert_eq!(rdp_file.to_string(), "use multimon:i:1\r\n");
+    }
+
+    #[test]
+    fn matches_keys_case_insensitively() {
+        let rdp_file = RdpFile::parse("Full Address:s:host\r\n");
+
+        assert_eq!(rdp_file.get_string("full address"), Some("host"));
+        assert_eq!(rdp_file.get_string("FULL ADDRESS "), Some("host"));
+    }
+
+    #[test]
+    fn parses_value_types() {
+        let rdp_file =
+            RdpFile::parse("a:i: 42\r\nb:s:some: text\r\nc:b:00FFa0\r\nd:i:nope\r\ne:b:ABC\r\n");
+
+        assert_eq!(rdp_file.get("a"), Some(&RdpValue::Integer(42)));
+        assert_eq!(rdp_file.get_string("b"), Some("some: text"));
+        assert_eq!(
+            rdp_file.get("c"),
+            Some(&RdpValue::Binary(vec![0x00, 0xff, 0xa0]))
+        );
+        assert_eq!(rdp_file.get("d"), None);
+        assert_eq!(rdp_file.get("e"), None);
+        assert_eq!(
+            rdp_file.invalid_lines().collect::<Vec<(usize, &str)>>(),
+            vec![(4, "d:i:nope"), (5, "e:b:ABC")]
+        );
+    }
+
+    #[test]
+    fn formats_values_back() {
+        let mut rdp_file = RdpFile::new();
+        rdp_file.set("c", RdpValue::Binary(vec![0x00, 0xff, 0xa0]));
+
+        assert_eq!(rdp_file.to_string(), "c:b:00FFA0\r\n");
+    }
+
+    #[test]
+    fn removes_every_occurrence() {
+        let mut rdp_file = RdpFile::parse("a:i:1\r\nb:i:2\r\nA:i:3\r\n");
+
+        assert_eq!(rdp_file.remove("a"), Some(RdpValue::Integer(1)));
+        assert_eq!(rdp_file.remove("a"), None);
+        assert_eq!(rdp_file.to_string(), "b:i:2\r\n");
+    }
+
+    #[test]
+    fn get_integer_ignores_other_types() {
+        let rdp_file = RdpFile::parse("a:s:1\r\n");
+
+        assert_eq!(rdp_file.get_integer("a"), None);
+        assert_eq!(rdp_file.get_string("a"), Some("1"));
+    }
+
+    #[test]
+    fn set_encoding_changes_the_bytes_only() {
+        let mut rdp_file = RdpFile::parse("a:i:1\r\n");
+        rdp_file.set_encoding(Encoding::Utf16Le);
+
+        assert_eq!(rdp_file.encoding(), Encoding::Utf16Le);
+        assert_eq!(rdp_file.to_bytes(), Encoding::Utf16Le.encode("a:i:1\r\n"));
+        assert_eq!(rdp_file.to_string(), "a:i:1\r\n");
+    }
+
+    const TEXT: &str = "full address:s:hôst\r\n";
+
+    fn utf16(text: &str, to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        text.encode_utf16().flat_map(to_bytes).collect()
+    }
+
+    fn assert_round_trip(bytes: &[u8], encoding: Encoding) {
+        assert_eq!(Encoding::detect(bytes), encoding);
+        assert_eq!(encoding.decode(bytes).unwrap(), TEXT);
+
+        let rdp_file = RdpFile::from_bytes(bytes).unwrap();
+        assert_eq!(rdp_file.encoding(), encoding);
+        assert_eq!(rdp_file.get_string("full address"), Some("hôst"));
+    }
+
+    #[test]
+    fn round_trips_utf8() {
+        let bytes = TEXT.as_bytes();
+
+        assert_round_trip(bytes, Encoding::Utf8);
+        assert_eq!(Encoding::Utf8.encode(TEXT), bytes);
+    }
+
+    #[test]
+    fn round_trips_utf8_with_bom() {
+        let bytes = [&UTF8_BOM[..], TEXT.as_bytes()].concat();
+
+        assert_round_trip(&bytes, Encoding::Utf8Bom);
+        assert_eq!(Encoding::Utf8Bom.encode(TEXT), bytes);
+    }
+
+    #[test]
+    fn round_trips_utf16_le_with_bom() {
+        let bytes = [&UTF16_LE_BOM[..], &utf16(TEXT, u16::to_le_bytes)].concat();
+
+        assert_round_trip(&bytes, Encoding::Utf16LeBom);
+        assert_eq!(Encoding::Utf16LeBom.encode(TEXT), bytes);
+    }
+
+    #[test]
+    fn round_trips_utf16_le_without_bom() {
+        let bytes = utf16(TEXT, u16::to_le_bytes);
+
+        assert_round_trip(&bytes, Encoding::Utf16Le);
+        assert_eq!(Encoding::Utf16Le.encode(TEXT), bytes);
+    }
+
+    #[test]
+    fn round_trips_utf16_be() {
+        let bytes = [&UTF16_BE_BOM[..], &utf16(TEXT, u16::to_be_bytes)].concat();
+
+        assert_round_trip(&bytes, Encoding::Utf16BeBom);
+        assert_eq!(Encoding::Utf16BeBom.encode(TEXT), bytes);
+
+        let without_bom = utf16(TEXT, u16::to_be_bytes);
+        assert_round_trip(&without_bom, Encoding::Utf16Be);
+        assert_eq!(Encoding::Utf16Be.encode(TEXT), without_bom);
+    }
+
+    #[test]
+    fn writes_the_detected_encoding_back() {
+        for bytes in [
+            TEXT.as_bytes().to_vec(),
+            [&UTF8_BOM[..], TEXT.as_bytes()].concat(),
+            utf16(TEXT, u16::to_le_bytes),
+            [&UTF16_LE_BOM[..], &utf16(TEXT, u16::to_le_bytes)].concat(),
+            utf16(TEXT, u16::to_be_bytes),
+            [&UTF16_BE_BOM[..], &utf16(TEXT, u16::to_be_bytes)].concat(),
+        ] {
+            assert_eq!(RdpFile::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+        }
+    }
+
+    #[test]
+    fn rejects_an_odd_byte_count() {
+        let mut bytes = [&UTF16_LE_BOM[..], &utf16(TEXT, u16::to_le_bytes)].concat();
+        bytes.push(b'a');
+
+        let err = Encoding::Utf16Le.decode(&bytes).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(RdpFile::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_utf16() {
+        // a lone high surrogate
+        let bytes = [&UTF16_LE_BOM[..], &[b'a', 0, 0x00, 0xd8, b'b', 0][..]].concat();
+
+        let err = Encoding::Utf16Le.decode(&bytes).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_invalid_utf8() {
+        let err = Encoding::Utf8.decode(&[b'a', 0xff, b'b']).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
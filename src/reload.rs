@@ -0,0 +1,81 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fs,
+    path::PathBuf,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use druid::{ExtEventSink, Selector, Target};
+
+use crate::{
+    config::{Config, Directories},
+    error::Result,
+};
+
+pub const CONFIG_RELOADED: Selector<Config> = Selector::new("instant-desktop.config-reloaded");
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Configurations that fail to load are skipped, so that the app keeps the last one while the
+/// file is being edited.
+pub fn watch_config(
+    sink: ExtEventSink,
+    directories: &Directories,
+    load: impl Fn() -> Result<Config> + Send + 'static,
+) {
+    let paths = [directories.system_config_path(), directories.config_path()];
+
+    thread::spawn(move || {
+        let mut last_modified = modified_times(&paths);
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let modified = modified_times(&paths);
+
+            if modified == last_modified {
+                continue;
+            }
+
+            last_modified = modified;
+
+            match load() {
+                Ok(config) => {
+                    // the app is closed
+                    if sink
+                        .submit_command(CONFIG_RELOADED, config, Target::Global)
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+                Err(err) => eprintln!("error: failed to reload the configuration: {}", err),
+            }
+        }
+    });
+}
+
+fn modified_times(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .collect()
+}
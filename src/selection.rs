@@ -0,0 +1,158 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+
+use druid::im::Vector;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Directories, monitor::Monitor};
+
+/// Kept with the monitor topology, so that it isn't applied to a different desk.
+#[derive(Serialize, Deserialize)]
+pub struct LastSelection {
+    topology: String,
+    monitors: Vec<u32>,
+}
+
+impl LastSelection {
+    pub fn new(monitors: &Vector<Monitor>) -> Self {
+        Self {
+            topology: topology(monitors),
+            monitors: monitors
+                .iter()
+                .filter(|monitor| monitor.selected)
+                .map(|monitor| monitor.id)
+                .collect(),
+        }
+    }
+
+    pub fn load(directories: &Directories) -> Option<Self> {
+        let content = fs::read_to_string(directories.last_selection_path()).ok()?;
+
+        serde_yaml::from_str(&content).ok()
+    }
+
+    pub fn save(&self, directories: &Directories) {
+        // remembering the selection is a convenience, so failing to do it shouldn't stop the
+        // remote session from starting
+        if let Ok(content) = serde_yaml::to_string(self) {
+            let _ = fs::write(directories.last_selection_path(), content);
+        }
+    }
+
+    pub fn apply(&self, monitors: &mut Vector<Monitor>) {
+        if self.topology != topology(monitors) {
+            return;
+        }
+
+        for monitor in monitors.iter_mut() {
+            monitor.selected = self.monitors.contains(&monitor.id);
+        }
+    }
+}
+
+fn topology(monitors: &Vector<Monitor>) -> String {
+    let mut monitors = monitors.iter().collect::<Vec<&Monitor>>();
+    monitors.sort_by_key(|monitor| monitor.id);
+
+    monitors
+        .iter()
+        .map(|monitor| {
+            format!(
+                "{}:{},{},{},{}",
+                monitor.id,
+                monitor.left(),
+                monitor.top(),
+                monitor.right(),
+                monitor.bottom()
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::MonitorRect;
+
+    fn monitors(second_left: i32) -> Vector<Monitor> {
+        [(1, 0), (2, second_left)]
+            .into_iter()
+            .map(|(id, left)| {
+                let rect = MonitorRect::new(left, 0, left + 1920, 1080);
+                Monitor::new(id, rect, rect, id == 1)
+            })
+            .collect()
+    }
+
+    fn selected_ids(monitors: &Vector<Monitor>) -> Vec<u32> {
+        monitors
+            .iter()
+            .filter(|monitor| monitor.selected)
+            .map(|monitor| monitor.id)
+            .collect()
+    }
+
+    fn read(content: &str) -> LastSelection {
+        serde_yaml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn restores_the_selection_on_the_same_topology() {
+        let mut selected = monitors(1920);
+        selected[1].selected = true;
+        let last_selection = read(&serde_yaml::to_string(&LastSelection::new(&selected)).unwrap());
+
+        let mut monitors = monitors(1920);
+        monitors[0].selected = true;
+        last_selection.apply(&mut monitors);
+
+        assert_eq!(selected_ids(&monitors), [2]);
+    }
+
+    #[test]
+    fn ignores_the_selection_of_another_topology() {
+        let mut selected = monitors(1920);
+        selected[1].selected = true;
+        let last_selection = LastSelection::new(&selected);
+
+        // the second monitor moved to the left of the first one
+        let mut moved = monitors(-1920);
+        moved[0].selected = true;
+        last_selection.apply(&mut moved);
+        assert_eq!(selected_ids(&moved), [1]);
+
+        // the second monitor was unplugged
+        let mut unplugged = monitors(1920);
+        unplugged.remove(1);
+        last_selection.apply(&mut unplugged);
+        assert!(selected_ids(&unplugged).is_empty());
+    }
+
+    #[test]
+    fn ignores_the_ids_that_no_longer_exist() {
+        let monitors = monitors(1920);
+        let last_selection = read(&format!(
+            "topology: \"{}\"\nmonitors: [2, 7]\n",
+            topology(&monitors)
+        ));
+
+        let mut restored = monitors.clone();
+        last_selection.apply(&mut restored);
+
+        assert_eq!(selected_ids(&restored), [2]);
+    }
+}
@@ -0,0 +1,355 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use druid::{
+    im::Vector,
+    widget::{
+        Button, Checkbox, CrossAxisAlignment, Either, Flex, Label, LineBreaking, List, RadioGroup,
+        Scroll, SizedBox, TextBox,
+    },
+    Data, Lens, Selector, Widget, WidgetExt,
+};
+
+use crate::{
+    config::{Config, Preset},
+    monitor::join_ids,
+    palette,
+    theme::ThemeKind,
+};
+
+pub const SAVE_SETTINGS: Selector = Selector::new("instant-desktop.save-settings");
+pub const CLOSE_SETTINGS: Selector = Selector::new("instant-desktop.close-settings");
+pub const ADD_PRESET: Selector = Selector::new("instant-desktop.add-preset");
+
+#[derive(Clone, Data, Lens, Default)]
+pub struct Settings {
+    pub base_config_path: String,
+    pub fullscreen: bool,
+    pub edit_connection: bool,
+    pub show_details: bool,
+    pub theme: ThemeKind,
+    pub presets: Vector<PresetSettings>,
+    pub new_preset_name: String,
+    pub error: String,
+}
+
+#[derive(Clone, Data, Lens)]
+pub struct PresetSettings {
+    pub name: String,
+    /// Comma-separated monitor IDs.
+    pub monitors: String,
+    pub base_config_path: String,
+    pub removed: bool,
+}
+
+impl Settings {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            base_config_path: config.base_config_path.to_string_lossy().into_owned(),
+            fullscreen: config.fullscreen,
+            edit_connection: config.edit_connection,
+            show_details: config.show_details,
+            theme: config.theme.base,
+            presets: config
+                .presets
+                .iter()
+                .map(|preset| PresetSettings {
+                    name: preset.name.clone(),
+                    monitors: join_ids(&preset.monitors),
+                    base_config_path: preset
+                        .base_config_path
+                        .as_ref()
+                        .map(|path| path.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    removed: false,
+                })
+                .collect(),
+            new_preset_name: String::new(),
+            error: String::new(),
+        }
+    }
+
+    pub fn add_preset(&mut self, monitors: &[u32]) {
+        let name = match self.new_preset_name.trim() {
+            "" => format!("monitors {}", join_ids(monitors)),
+            name => name.to_string(),
+        };
+
+        self.presets.push_back(PresetSettings {
+            name,
+            monitors: join_ids(monitors),
+            base_config_path: String::new(),
+            removed: false,
+        });
+        self.new_preset_name.clear();
+    }
+
+    pub fn apply(&self, config: &Config) -> Result<Config, String> {
+        let mut config = config.clone();
+
+        config.base_config_path = PathBuf::from(self.base_config_path.trim());
+        config.fullscreen = self.fullscreen;
+        config.edit_connection = self.edit_connection;
+        config.show_details = self.show_details;
+        config.theme.base = self.theme;
+        config.presets = self
+            .presets
+            .iter()
+            .filter(|preset| !preset.removed)
+            .map(PresetSettings::to_preset)
+            .collect::<Result<Vec<Preset>, String>>()?;
+
+        Ok(config)
+    }
+}
+
+impl PresetSettings {
+    fn to_preset(&self) -> Result<Preset, String> {
+        let ids = self
+            .monitors
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| id.parse())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|_| {
+                format!(
+                    "The monitors of the preset \"{}\" must be IDs separated by commas.",
+                    self.name.trim()
+                )
+            })?;
+
+        // a monitor typed twice is only selected once
+        let mut monitors = Vec::new();
+        for id in ids {
+            if !monitors.contains(&id) {
+                monitors.push(id);
+            }
+        }
+
+        let base_config_path = match self.base_config_path.trim() {
+            "" => None,
+            path => Some(PathBuf::from(path)),
+        };
+
+        Ok(Preset {
+            name: self.name.trim().to_string(),
+            monitors,
+            base_config_path,
+        })
+    }
+}
+
+pub fn settings_builder() -> impl Widget<Settings> {
+    let base_config_path = Flex::row()
+        .with_child(Label::new("Base RDP file").fix_width(150.0))
+        .with_flex_child(
+            TextBox::new()
+                .with_placeholder("C:\\Users\\you\\Documents\\Default.rdp")
+                .expand_width()
+                .lens(Settings::base_config_path),
+            1.0,
+        );
+
+    let presets = Scroll::new(List::new(preset_builder).lens(Settings::presets))
+        .vertical()
+        .fix_height(200.0);
+
+    let new_preset = Flex::row()
+        .with_flex_child(
+            TextBox::new()
+                .with_placeholder("New preset name")
+                .expand_width()
+                .lens(Settings::new_preset_name),
+            1.0,
+        )
+        .with_spacer(10.0)
+        .with_child(
+            Button::new("Add the selected monitors")
+                .on_click(|ctx, _, _| ctx.submit_command(ADD_PRESET)),
+        );
+
+    let error = Either::new(
+        |data: &Settings, _| data.error.is_empty(),
+        SizedBox::empty(),
+        Label::dynamic(|data: &Settings, _| data.error.clone())
+            .with_text_color(palette::WARNING_TEXT_COLOR)
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .padding((0.0, 10.0)),
+    );
+
+    let buttons = Flex::row()
+        .with_child(Button::new("Save").on_click(|ctx, _, _| ctx.submit_command(SAVE_SETTINGS)))
+        .with_spacer(10.0)
+        .with_child(Button::new("Cancel").on_click(|ctx, _, _| ctx.submit_command(CLOSE_SETTINGS)));
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new("Settings").with_text_size(32.0))
+        .with_spacer(20.0)
+        .with_child(base_config_path)
+        .with_spacer(10.0)
+        .with_child(
+            Checkbox::new("Cover the whole monitors, taskbar included").lens(Settings::fullscreen),
+        )
+        .with_spacer(10.0)
+        .with_child(
+            Checkbox::new("Edit the connection before starting it").lens(Settings::edit_connection),
+        )
+        .with_spacer(10.0)
+        .with_child(
+            Checkbox::new("Show the details of the monitors under their ID")
+                .lens(Settings::show_details),
+        )
+        .with_spacer(20.0)
+        .with_child(
+            Flex::row()
+                .with_child(Label::new("Theme").fix_width(150.0))
+                .with_child(
+                    RadioGroup::row(ThemeKind::ALL.map(|theme| (theme.name(), theme)))
+                        .lens(Settings::theme),
+                ),
+        )
+        .with_spacer(20.0)
+        .with_child(Label::new("Presets (F1 to F9)").with_text_size(20.0))
+        .with_spacer(10.0)
+        .with_child(presets)
+        .with_spacer(10.0)
+        .with_child(new_preset)
+        .with_child(error)
+        .with_spacer(20.0)
+        .with_child(buttons)
+        .padding(30.0)
+        .fix_width(800.0)
+        .background(palette::PANEL_BACKGROUND_COLOR)
+}
+
+fn preset_builder() -> impl Widget<PresetSettings> {
+    let row = Flex::row()
+        .with_child(
+            TextBox::new()
+                .with_placeholder("Name")
+                .fix_width(180.0)
+                .lens(PresetSettings::name),
+        )
+        .with_spacer(10.0)
+        .with_child(
+            TextBox::new()
+                .with_placeholder("1,2")
+                .fix_width(100.0)
+                .lens(PresetSettings::monitors),
+        )
+        .with_spacer(10.0)
+        .with_flex_child(
+            TextBox::new()
+                .with_placeholder("Base RDP file (optional)")
+                .expand_width()
+                .lens(PresetSettings::base_config_path),
+            1.0,
+        )
+        .with_spacer(10.0)
+        .with_child(
+            Button::new("Remove").on_click(|_, data: &mut PresetSettings, _| data.removed = true),
+        )
+        .padding((0.0, 5.0));
+
+    Either::new(
+        |data: &PresetSettings, _| data.removed,
+        SizedBox::empty(),
+        row,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Directories;
+
+    fn preset(monitors: &str) -> PresetSettings {
+        PresetSettings {
+            name: String::from(" left pair "),
+            monitors: monitors.to_string(),
+            base_config_path: String::new(),
+            removed: false,
+        }
+    }
+
+    #[test]
+    fn parses_the_monitor_ids() {
+        let preset = preset("1,2").to_preset().unwrap();
+
+        assert_eq!(preset.name, "left pair");
+        assert_eq!(preset.monitors, [1, 2]);
+        assert_eq!(preset.base_config_path, None);
+    }
+
+    #[test]
+    fn ignores_whitespace_and_empty_ids() {
+        assert_eq!(
+            preset(" 3 , 1,, 2 ,").to_preset().unwrap().monitors,
+            [3, 1, 2]
+        );
+        assert!(preset(" ").to_preset().unwrap().monitors.is_empty());
+    }
+
+    #[test]
+    fn keeps_the_first_of_duplicate_ids() {
+        assert_eq!(preset("2,1,2,1").to_preset().unwrap().monitors, [2, 1]);
+    }
+
+    #[test]
+    fn rejects_non_numeric_ids() {
+        for monitors in ["1;2", "one", "1,-2", "1 2"] {
+            assert_eq!(
+                preset(monitors).to_preset().err().as_deref(),
+                Some("The monitors of the preset \"left pair\" must be IDs separated by commas."),
+                "{}",
+                monitors
+            );
+        }
+    }
+
+    #[test]
+    fn joins_the_ids_back() {
+        assert_eq!(join_ids(&[1, 2, 3]), "1,2,3");
+        assert_eq!(join_ids(&[]), "");
+    }
+
+    #[test]
+    fn applies_the_presets_that_are_kept() {
+        let config = Config::new(Directories::default());
+        let mut settings = Settings::new(&config);
+        settings.presets.push_back(preset("1, 2"));
+        settings.presets.push_back(PresetSettings {
+            removed: true,
+            ..preset("x")
+        });
+        settings.presets.push_back(PresetSettings {
+            base_config_path: String::from(" C:\\right.rdp "),
+            ..preset("2")
+        });
+
+        let applied = settings.apply(&config).unwrap();
+        assert_eq!(applied.presets.len(), config.presets.len() + 2);
+        assert_eq!(
+            applied.presets.last().unwrap().base_config_path,
+            Some(PathBuf::from("C:\\right.rdp"))
+        );
+
+        settings.presets.push_back(preset("1,a"));
+        assert!(settings.apply(&config).is_err());
+    }
+}
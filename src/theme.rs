@@ -0,0 +1,254 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use druid::{Color, Data};
+use serde::{Deserialize, Serialize};
+
+const ID_FONT_SIZE: f64 = 200.0;
+const ID_BOX_SIZE: f64 = 300.0;
+
+#[derive(Serialize, Deserialize, Data, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeKind {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl ThemeKind {
+    pub const ALL: [ThemeKind; 3] = [ThemeKind::Dark, ThemeKind::Light, ThemeKind::HighContrast];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemeKind::Dark => "Dark",
+            ThemeKind::Light => "Light",
+            ThemeKind::HighContrast => "High contrast",
+        }
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            ThemeKind::Dark => Palette {
+                primary: Color::rgb8(0xff, 0xd2, 0x00),
+                light: Color::rgb8(0x8a, 0x91, 0x99),
+                light_hovered: Color::rgb8(0xbf, 0xc4, 0xca),
+                dark: Color::rgb8(0x33, 0x31, 0x32),
+                background: Color::BLACK,
+                text: Color::WHITE,
+                warning_background: Color::rgb8(0x33, 0x31, 0x32),
+                warning_text: Color::rgb8(0xff, 0xd2, 0x00),
+                panel_background: Color::rgb8(0x33, 0x31, 0x32),
+            },
+            ThemeKind::Light => Palette {
+                primary: Color::rgb8(0x00, 0x78, 0xd4),
+                light: Color::rgb8(0x6b, 0x72, 0x80),
+                light_hovered: Color::rgb8(0x4b, 0x55, 0x63),
+                dark: Color::rgb8(0x1f, 0x29, 0x33),
+                background: Color::rgb8(0xf3, 0xf4, 0xf6),
+                text: Color::WHITE,
+                warning_background: Color::rgb8(0xff, 0xf4, 0xce),
+                warning_text: Color::rgb8(0x1f, 0x29, 0x33),
+                panel_background: Color::rgb8(0x1f, 0x29, 0x33),
+            },
+            ThemeKind::HighContrast => Palette {
+                primary: Color::rgb8(0xff, 0xff, 0x00),
+                light: Color::rgb8(0x00, 0x00, 0xff),
+                light_hovered: Color::rgb8(0x80, 0x00, 0xff),
+                dark: Color::BLACK,
+                background: Color::BLACK,
+                text: Color::WHITE,
+                warning_background: Color::BLACK,
+                warning_text: Color::rgb8(0xff, 0xff, 0x00),
+                panel_background: Color::BLACK,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Theme {
+    pub base: ThemeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub light: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub light_hovered: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dark: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning_background: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub panel_background: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_font_size: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_box_size: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Palette {
+    pub primary: Color,
+    pub light: Color,
+    pub light_hovered: Color,
+    pub dark: Color,
+    pub background: Color,
+    /// The IDs, and the ID box of the hovered monitor when it's selected.
+    pub text: Color,
+    pub warning_background: Color,
+    pub warning_text: Color,
+    pub panel_background: Color,
+}
+
+impl Theme {
+    pub fn colors(&self) -> [(&'static str, Option<&str>); 9] {
+        [
+            ("primary", self.primary.as_deref()),
+            ("light", self.light.as_deref()),
+            ("light_hovered", self.light_hovered.as_deref()),
+            ("dark", self.dark.as_deref()),
+            ("background", self.background.as_deref()),
+            ("text", self.text.as_deref()),
+            ("warning_background", self.warning_background.as_deref()),
+            ("warning_text", self.warning_text.as_deref()),
+            ("panel_background", self.panel_background.as_deref()),
+        ]
+    }
+
+    /// Invalid colors keep the color of the base theme.
+    pub fn palette(&self) -> Palette {
+        let base = self.base.palette();
+        let color = |hex: &Option<String>, default: Color| {
+            hex.as_deref()
+                .and_then(|hex| parse_color(hex).ok())
+                .unwrap_or(default)
+        };
+
+        Palette {
+            primary: color(&self.primary, base.primary),
+            light: color(&self.light, base.light),
+            light_hovered: color(&self.light_hovered, base.light_hovered),
+            dark: color(&self.dark, base.dark),
+            background: color(&self.background, base.background),
+            text: color(&self.text, base.text),
+            warning_background: color(&self.warning_background, base.warning_background),
+            warning_text: color(&self.warning_text, base.warning_text),
+            panel_background: color(&self.panel_background, base.panel_background),
+        }
+    }
+
+    pub fn id_font_size(&self) -> f64 {
+        self.id_font_size.unwrap_or(ID_FONT_SIZE)
+    }
+
+    pub fn id_box_size(&self) -> f64 {
+        self.id_box_size.unwrap_or(ID_BOX_SIZE)
+    }
+}
+
+pub fn parse_color(hex: &str) -> Result<Color, String> {
+    if !hex.starts_with('#') {
+        return Err(format!("\"{}\" must start with #, like #ffd200", hex));
+    }
+
+    Color::from_hex_str(hex).map_err(|_| {
+        format!(
+            "\"{}\" isn't a hex color like #ffd200, #fd0 or #ffd20080",
+            hex
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors(palette: Palette) -> [u32; 9] {
+        [
+            palette.primary,
+            palette.light,
+            palette.light_hovered,
+            palette.dark,
+            palette.background,
+            palette.text,
+            palette.warning_background,
+            palette.warning_text,
+            palette.panel_background,
+        ]
+        .map(|color| color.as_rgba_u32())
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        for (hex, rgba) in [
+            ("#fd0", 0xffdd00ff),
+            ("#fd08", 0xffdd0088),
+            ("#ffd200", 0xffd200ff),
+            ("#FFD200", 0xffd200ff),
+            ("#ffd20080", 0xffd20080),
+        ] {
+            assert_eq!(
+                parse_color(hex).map(|color| color.as_rgba_u32()),
+                Ok(rgba),
+                "{}",
+                hex
+            );
+        }
+    }
+
+    #[test]
+    fn requires_the_hash() {
+        assert!(parse_color("ffd200")
+            .unwrap_err()
+            .contains("must start with #"));
+        assert!(parse_color("yellow")
+            .unwrap_err()
+            .contains("must start with #"));
+    }
+
+    #[test]
+    fn rejects_bad_hex_digits() {
+        for hex in ["#", "#ff", "#ffd20", "#ggd200", "#ffd200801"] {
+            assert!(parse_color(hex).is_err(), "{}", hex);
+        }
+    }
+
+    #[test]
+    fn replaces_only_the_overridden_colors() {
+        let theme = Theme {
+            base: ThemeKind::Light,
+            primary: Some(String::from("#123456")),
+            // an invalid color keeps the base one
+            dark: Some(String::from("123456")),
+            ..Default::default()
+        };
+
+        let mut expected = colors(ThemeKind::Light.palette());
+        expected[0] = 0x123456ff;
+
+        assert_eq!(colors(theme.palette()), expected);
+        assert_eq!(
+            colors(Theme::default().palette()),
+            colors(ThemeKind::Dark.palette())
+        );
+    }
+}
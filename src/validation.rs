@@ -0,0 +1,219 @@
+// Copyright 2023 Dara Kong
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use crate::monitor::{Monitor, MonitorRect};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectionWarning {
+    MixedResolutions(Vec<(i32, i32)>),
+    MixedScaling(Vec<f64>),
+    Disconnected(Vec<Vec<u32>>),
+    MissingPrimary,
+}
+
+impl fmt::Display for SelectionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectionWarning::MixedResolutions(resolutions) => write!(
+                f,
+                "The selected monitors have different resolutions ({}).",
+                resolutions
+                    .iter()
+                    .map(|(width, height)| format!("{} x {}", width, height))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            SelectionWarning::MixedScaling(scales) => write!(
+                f,
+                "The selected monitors have different scaling ({}).",
+                scales
+                    .iter()
+                    .map(|scale| format!("{}%", (scale * 100.0).round()))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            SelectionWarning::Disconnected(groups) => write!(
+                f,
+                "The selected monitors don't touch each other ({}).",
+                groups
+                    .iter()
+                    .map(|group| {
+                        group
+                            .iter()
+                            .map(|id| id.to_string())
+                            .collect::<Vec<String>>()
+                            .join(" + ")
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            SelectionWarning::MissingPrimary => write!(f, "The primary monitor isn't selected."),
+        }
+    }
+}
+
+pub fn validate_selection<'a>(
+    selected: impl IntoIterator<Item = &'a Monitor>,
+) -> Vec<SelectionWarning> {
+    let selected = selected.into_iter().collect::<Vec<&Monitor>>();
+    let mut warnings = Vec::new();
+
+    if selected.is_empty() {
+        return warnings;
+    }
+
+    let mut resolutions = Vec::new();
+    let mut scales = Vec::new();
+
+    for monitor in &selected {
+        // in physical pixels, as the remote session uses them whatever the scale
+        let resolution = (monitor.width(), monitor.height());
+
+        if !resolutions.contains(&resolution) {
+            resolutions.push(resolution);
+        }
+
+        if !scales.contains(&monitor.scale()) {
+            scales.push(monitor.scale());
+        }
+    }
+
+    if resolutions.len() > 1 {
+        warnings.push(SelectionWarning::MixedResolutions(resolutions));
+    }
+
+    if scales.len() > 1 {
+        warnings.push(SelectionWarning::MixedScaling(scales));
+    }
+
+    let groups = connected_groups(&selected);
+
+    if groups.len() > 1 {
+        warnings.push(SelectionWarning::Disconnected(groups));
+    }
+
+    if !selected.iter().any(|monitor| monitor.is_primary()) {
+        warnings.push(SelectionWarning::MissingPrimary);
+    }
+
+    warnings
+}
+
+fn connected_groups(monitors: &[&Monitor]) -> Vec<Vec<u32>> {
+    let mut groups: Vec<Vec<u32>> = Vec::new();
+    let mut visited = vec![false; monitors.len()];
+
+    for start in 0..monitors.len() {
+        if visited[start] {
+            continue;
+        }
+
+        let mut group = Vec::new();
+        let mut stack = vec![start];
+        visited[start] = true;
+
+        while let Some(current) = stack.pop() {
+            group.push(monitors[current].id);
+
+            for next in 0..monitors.len() {
+                if !visited[next] && touches(monitors[current].rect(), monitors[next].rect()) {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        group.sort();
+        groups.push(group);
+    }
+
+    groups
+}
+
+fn touches(a: MonitorRect, b: MonitorRect) -> bool {
+    let horizontal_overlap = a.right.min(b.right) - a.left.max(b.left);
+    let vertical_overlap = a.bottom.min(b.bottom) - a.top.max(b.top);
+
+    (horizontal_overlap >= 0 && vertical_overlap > 0)
+        || (horizontal_overlap > 0 && vertical_overlap >= 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::monitor::{MonitorProvider, StaticMonitorProvider};
+
+    fn validate_fixture(file: &str) -> Vec<SelectionWarning> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures/monitors")
+            .join(file);
+        let monitors = StaticMonitorProvider::from_fixture(path)
+            .and_then(|provider| provider.monitors())
+            .unwrap()
+            .list();
+
+        validate_selection(&monitors)
+    }
+
+    #[test]
+    fn warns_about_monitors_that_dont_touch() {
+        assert_eq!(
+            validate_fixture("gap.yaml"),
+            vec![SelectionWarning::Disconnected(vec![vec![0], vec![1]])]
+        );
+    }
+
+    #[test]
+    fn warns_about_mixed_scaling_and_resolutions() {
+        assert_eq!(
+            validate_fixture("mixed_scale.yaml"),
+            vec![
+                SelectionWarning::MixedResolutions(vec![(2880, 1800), (1920, 1080)]),
+                SelectionWarning::MixedScaling(vec![1.5, 1.0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_identical_monitors_side_by_side() {
+        assert_eq!(validate_fixture("side_by_side.yaml"), vec![]);
+    }
+
+    #[test]
+    fn warns_about_a_missing_primary_monitor() {
+        let path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/monitors/side_by_side.yaml");
+        let monitors = StaticMonitorProvider::from_fixture(path)
+            .and_then(|provider| provider.monitors())
+            .unwrap()
+            .list();
+
+        assert_eq!(
+            validate_selection(monitors.iter().filter(|monitor| monitor.id != 0)),
+            vec![
+                SelectionWarning::Disconnected(vec![vec![1], vec![2]]),
+                SelectionWarning::MissingPrimary,
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_an_empty_selection() {
+        assert_eq!(validate_selection(&[]), vec![]);
+    }
+}
@@ -12,126 +12,58 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use druid::{im::Vector, Data};
-use widestring::U16CString;
+use std::{collections::HashMap, mem};
+
+use widestring::{U16CStr, U16CString};
 use windows::{
     core::{w, PCWSTR},
     Win32::{
+        Devices::Display::{
+            DisplayConfigGetDeviceInfo, GetDisplayConfigBufferSizes, QueryDisplayConfig,
+            DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME, DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+            DISPLAYCONFIG_DEVICE_INFO_HEADER, DISPLAYCONFIG_MODE_INFO, DISPLAYCONFIG_PATH_INFO,
+            DISPLAYCONFIG_SOURCE_DEVICE_NAME, DISPLAYCONFIG_TARGET_DEVICE_NAME,
+            QDC_ONLY_ACTIVE_PATHS,
+        },
         Foundation::{BOOL, HINSTANCE, HWND, LPARAM, RECT},
         Graphics::Gdi::{
             self, DISPLAY_DEVICEW, DISPLAY_DEVICE_ACTIVE, HDC, HMONITOR, MONITORINFOEXW,
         },
+        System::Console::{AttachConsole, ATTACH_PARENT_PROCESS},
         UI::{
-            HiDpi::{SetProcessDpiAwareness, PROCESS_DPI_UNAWARE},
+            HiDpi::{
+                GetDpiForMonitor, SetProcessDpiAwareness, SetProcessDpiAwarenessContext,
+                DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2, MDT_EFFECTIVE_DPI,
+                PROCESS_PER_MONITOR_DPI_AWARE,
+            },
             Shell,
-            WindowsAndMessaging::MB_ICONASTERISK,
+            WindowsAndMessaging::{
+                self, MB_ICONASTERISK, MB_ICONERROR, MB_OK, MONITORINFOF_PRIMARY,
+            },
         },
     },
 };
 
-#[derive(Clone, Data)]
-pub struct Monitor {
-    pub id: u32,
-    pub selected: bool,
-    #[data(ignore)]
-    display_monitor_handle: HMONITOR,
-    #[data(ignore)]
-    monitor_info: MONITORINFOEXW,
-}
-
-impl Monitor {
-    pub fn new(id: u32) -> Self {
-        Monitor {
-            id,
-            selected: false,
-            display_monitor_handle: HMONITOR::default(),
-            monitor_info: MONITORINFOEXW::default(),
-        }
-    }
-
-    pub fn id(&self) -> u32 {
-        self.id
-    }
-
-    pub fn left(&self) -> i32 {
-        self.monitor_info.monitorInfo.rcMonitor.left
-    }
-
-    pub fn top(&self) -> i32 {
-        self.monitor_info.monitorInfo.rcMonitor.top
-    }
-
-    pub fn right(&self) -> i32 {
-        self.monitor_info.monitorInfo.rcMonitor.right
-    }
-
-    pub fn bottom(&self) -> i32 {
-        self.monitor_info.monitorInfo.rcMonitor.bottom
-    }
-
-    pub fn width(&self) -> i32 {
-        self.right() - self.left()
-    }
-
-    pub fn height(&self) -> i32 {
-        self.bottom() - self.top()
-    }
-
-    pub fn work_left(&self) -> i32 {
-        self.monitor_info.monitorInfo.rcWork.left
-    }
-
-    pub fn work_top(&self) -> i32 {
-        self.monitor_info.monitorInfo.rcWork.top
-    }
-
-    pub fn work_right(&self) -> i32 {
-        self.monitor_info.monitorInfo.rcWork.right
-    }
-
-    pub fn work_bottom(&self) -> i32 {
-        self.monitor_info.monitorInfo.rcWork.bottom
-    }
-
-    pub fn work_width(&self) -> i32 {
-        self.work_right() - self.work_left()
-    }
-
-    pub fn work_height(&self) -> i32 {
-        self.work_bottom() - self.work_top()
-    }
+use crate::{
+    error::Result,
+    monitor::{Monitor, MonitorProvider, MonitorRect, Monitors, BASE_DPI},
+};
 
-    pub fn info_str(&self) -> String {
-        format!(
-            "{}: {} x {}; ({}, {}, {}, {})",
-            self.id(),
-            self.width(),
-            self.height(),
-            self.left(),
-            self.top(),
-            self.right() - 1,
-            self.bottom() - 1
-        )
-    }
-}
+/// The process is made per-monitor DPI aware first, so that the monitors are reported in
+/// physical pixels along with their effective DPI.
+pub struct Win32MonitorProvider;
 
-#[derive(Clone, Data)]
-pub struct Monitors {
-    list: Vector<Monitor>,
+#[derive(Default)]
+struct MonitorTarget {
+    info: MONITORINFOEXW,
+    handle: HMONITOR,
 }
 
-impl Monitors {
-    pub fn new() -> Self {
-        Self {
-            list: Vector::new(),
-        }
-    }
+impl MonitorProvider for Win32MonitorProvider {
+    fn monitors(&self) -> Result<Monitors> {
+        set_dpi_awareness();
 
-    pub fn enum_active() -> Self {
-        unsafe {
-            SetProcessDpiAwareness(PROCESS_DPI_UNAWARE)
-                .expect("SetProcessDpiAwareness should succeed");
-        }
+        let names = display_names();
 
         let mut active_monitors_list = Monitors::new();
 
@@ -150,69 +82,243 @@ impl Monitors {
             }
 
             if display_device.StateFlags & DISPLAY_DEVICE_ACTIVE == 1 {
-                let mut monitor = Monitor::new(i);
+                let mut target = MonitorTarget {
+                    info: MONITORINFOEXW {
+                        szDevice: display_device.DeviceName,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
 
-                monitor.monitor_info.szDevice = display_device.DeviceName;
-
-                let monitor_ptr = LPARAM(&mut monitor as *mut _ as isize);
+                let target_ptr = LPARAM(&mut target as *mut _ as isize);
 
                 unsafe {
                     Gdi::EnumDisplayMonitors(
                         HDC::default(),
                         None,
                         Some(monitor_enum_proc),
-                        monitor_ptr,
+                        target_ptr,
                     );
                 }
 
-                if monitor.monitor_info.monitorInfo.cbSize != 0 {
-                    active_monitors_list.add(monitor);
+                if target.info.monitorInfo.cbSize != 0 {
+                    let info = target.info.monitorInfo;
+
+                    active_monitors_list.add(
+                        Monitor::new(
+                            i,
+                            monitor_rect(info.rcMonitor),
+                            monitor_rect(info.rcWork),
+                            info.dwFlags & MONITORINFOF_PRIMARY != 0,
+                        )
+                        .with_name(monitor_name(&display_device, &names))
+                        .with_dpi(monitor_dpi(target.handle)),
+                    );
                 }
             }
 
             i += 1;
         }
 
-        active_monitors_list
+        Ok(active_monitors_list)
     }
+}
+
+pub fn display_list(monitors: &Monitors) {
+    let wide_text = U16CString::from_str_truncate(
+        monitors
+            .list()
+            .iter()
+            .map(|mon| mon.info_str())
+            .collect::<Vec<String>>()
+            .join("\n"),
+    );
 
-    pub fn add(&mut self, monitor: Monitor) {
-        self.list.push_back(monitor);
+    let text_ptr = PCWSTR::from_raw(wide_text.as_ptr());
+
+    unsafe {
+        Shell::ShellMessageBoxW(
+            HINSTANCE::default(),
+            HWND::default(),
+            text_ptr,
+            w!("Instant Desktop"),
+            MB_ICONASTERISK,
+        );
+    }
+}
+
+pub fn show_message(message: &str) {
+    let wide_text = U16CString::from_str_truncate(message);
+
+    unsafe {
+        WindowsAndMessaging::MessageBoxW(
+            HWND::default(),
+            PCWSTR::from_raw(wide_text.as_ptr()),
+            w!("Instant Desktop"),
+            MB_ICONASTERISK | MB_OK,
+        );
+    }
+}
+
+pub fn show_error(message: &str) {
+    let wide_text = U16CString::from_str_truncate(message);
+
+    unsafe {
+        WindowsAndMessaging::MessageBoxW(
+            HWND::default(),
+            PCWSTR::from_raw(wide_text.as_ptr()),
+            w!("Instant Desktop"),
+            MB_ICONERROR | MB_OK,
+        );
+    }
+}
+
+pub fn attach_console() {
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// Falls back to the older per-monitor awareness before Windows 10 1703. The errors are ignored,
+/// as the awareness can't be changed once set, by the application manifest for instance.
+fn set_dpi_awareness() {
+    unsafe {
+        if SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2).is_err() {
+            let _ = SetProcessDpiAwareness(PROCESS_PER_MONITOR_DPI_AWARE);
+        }
     }
+}
+
+fn monitor_dpi(handle: HMONITOR) -> u32 {
+    let (mut dpi_x, mut dpi_y) = (0, 0);
+
+    match unsafe { GetDpiForMonitor(handle, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) } {
+        Ok(()) if dpi_x != 0 => dpi_x,
+        _ => BASE_DPI,
+    }
+}
+
+/// Prefers the friendly name from the EDID to the generic `DeviceString`.
+fn monitor_name(adapter: &DISPLAY_DEVICEW, names: &HashMap<String, String>) -> Option<String> {
+    wide_string(&adapter.DeviceName)
+        .and_then(|device_name| names.get(&device_name).cloned())
+        .or_else(|| device_string(adapter))
+}
+
+/// Keyed by the GDI device name of their source, like `\\.\DISPLAY1`, which is how
+/// `EnumDisplayDevicesW` names the adapters.
+fn display_names() -> HashMap<String, String> {
+    let mut names = HashMap::new();
+
+    let (mut path_count, mut mode_count) = (0, 0);
 
-    pub fn list(&self) -> Vector<Monitor> {
-        self.list.clone()
+    if unsafe {
+        GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut path_count, &mut mode_count)
+    }
+    .is_err()
+    {
+        return names;
     }
 
-    pub fn display_list(&self) {
-        let wide_text = U16CString::from_str(
-            self.list()
-                .iter()
-                .map(|mon| mon.info_str())
-                .collect::<Vec<String>>()
-                .join("\n"),
+    let mut paths = vec![DISPLAYCONFIG_PATH_INFO::default(); path_count as usize];
+    let mut modes = vec![DISPLAYCONFIG_MODE_INFO::default(); mode_count as usize];
+
+    let result = unsafe {
+        QueryDisplayConfig(
+            QDC_ONLY_ACTIVE_PATHS,
+            &mut path_count,
+            paths.as_mut_ptr(),
+            &mut mode_count,
+            modes.as_mut_ptr(),
+            None,
         )
-        .expect("conversion from str to U16CString should work");
-
-        let text_ptr = PCWSTR::from_raw(wide_text.as_ptr());
-
-        unsafe {
-            Shell::ShellMessageBoxW(
-                HINSTANCE::default(),
-                HWND::default(),
-                text_ptr,
-                w!("Instant Desktop"),
-                MB_ICONASTERISK,
-            );
+    };
+
+    if result.is_err() {
+        return names;
+    }
+
+    paths.truncate(path_count as usize);
+
+    for path in paths {
+        let mut source = DISPLAYCONFIG_SOURCE_DEVICE_NAME {
+            header: DISPLAYCONFIG_DEVICE_INFO_HEADER {
+                r#type: DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME,
+                size: mem::size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>() as u32,
+                adapterId: path.sourceInfo.adapterId,
+                id: path.sourceInfo.id,
+            },
+            ..Default::default()
+        };
+
+        let mut target = DISPLAYCONFIG_TARGET_DEVICE_NAME {
+            header: DISPLAYCONFIG_DEVICE_INFO_HEADER {
+                r#type: DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+                size: mem::size_of::<DISPLAYCONFIG_TARGET_DEVICE_NAME>() as u32,
+                adapterId: path.targetInfo.adapterId,
+                id: path.targetInfo.id,
+            },
+            ..Default::default()
+        };
+
+        // both return a Win32 error code, 0 meaning success
+        if unsafe { DisplayConfigGetDeviceInfo(&mut source.header) } != 0
+            || unsafe { DisplayConfigGetDeviceInfo(&mut target.header) } != 0
+        {
+            continue;
+        }
+
+        let device_name = wide_string(&source.viewGdiDeviceName);
+        let name = wide_string(&target.monitorFriendlyDeviceName);
+
+        // internal panels usually have no friendly name, and a cloned source keeps its first
+        if let (Some(device_name), Some(name)) = (device_name, name) {
+            if !name.is_empty() {
+                names.entry(device_name).or_insert(name);
+            }
         }
     }
+
+    names
+}
+
+fn device_string(adapter: &DISPLAY_DEVICEW) -> Option<String> {
+    let mut display_device = DISPLAY_DEVICEW {
+        cb: 840,
+        ..Default::default()
+    };
+
+    let result = unsafe {
+        Gdi::EnumDisplayDevicesW(
+            PCWSTR::from_raw(adapter.DeviceName.as_ptr()),
+            0,
+            &mut display_device,
+            0,
+        )
+    };
+
+    if !result.as_bool() {
+        return None;
+    }
+
+    wide_string(&display_device.DeviceString)
+}
+
+fn wide_string(buffer: &[u16]) -> Option<String> {
+    U16CStr::from_slice_truncate(buffer)
+        .ok()
+        .map(|string| string.to_string_lossy())
+}
+
+fn monitor_rect(rect: RECT) -> MonitorRect {
+    MonitorRect::new(rect.left, rect.top, rect.right, rect.bottom)
 }
 
 unsafe extern "system" fn monitor_enum_proc(
     display_monitor_handle: HMONITOR,
     _: HDC,
     _: *mut RECT,
-    monitor_ptr: LPARAM,
+    target_ptr: LPARAM,
 ) -> BOOL {
     let mut monitor_info = MONITORINFOEXW::default();
     monitor_info.monitorInfo.cbSize = 104;
@@ -225,12 +331,12 @@ unsafe extern "system" fn monitor_enum_proc(
     let mut continue_enum = BOOL::from(true);
 
     if result.as_bool() {
-        let monitor = (monitor_ptr.0 as *mut Monitor).as_mut();
+        let target = (target_ptr.0 as *mut MonitorTarget).as_mut();
 
-        if let Some(monitor) = monitor {
-            if monitor_info.szDevice == monitor.monitor_info.szDevice {
-                monitor.display_monitor_handle = display_monitor_handle;
-                monitor.monitor_info = monitor_info;
+        if let Some(target) = target {
+            if monitor_info.szDevice == target.info.szDevice {
+                target.info = monitor_info;
+                target.handle = display_monitor_handle;
 
                 continue_enum = BOOL::from(false);
             }